@@ -4,11 +4,22 @@ use crate::day01::puzzle::Puzzle;
 
 pub(crate) fn run() {
     let filename = "data/day01/input.txt";
-    let puzzle = Puzzle::from_file(filename);
+    let puzzle = Puzzle::<i64>::from_file(filename);
 
-    let result_part_1 = puzzle.calculate_distance();
-    println!("Result of Day 01, Part 1 is {}", result_part_1);
+    match puzzle.calculate_distance() {
+        Ok(result_part_1) => println!("Result of Day 01, Part 1 is {}", result_part_1),
+        Err(error) => eprintln!("Error in Day 01, Part 1: {}", error),
+    }
 
-    let result_part_2 = puzzle.calculate_frequency();
-    println!("Result of Day 01, Part 2 is {}", result_part_2);
+    match puzzle.calculate_frequency() {
+        Ok(result_part_2) => println!("Result of Day 01, Part 2 is {}", result_part_2),
+        Err(error) => eprintln!("Error in Day 01, Part 2: {}", error),
+    }
+
+    if puzzle.column_count() > 2 {
+        match puzzle.comparison_matrix() {
+            Ok(matrix) => print!("{}", matrix),
+            Err(error) => eprintln!("Error in Day 01 comparison matrix: {}", error),
+        }
+    }
 }
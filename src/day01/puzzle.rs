@@ -1,57 +1,222 @@
-use crate::utils::line_integer_parser::parse_two_integers;
+use crate::utils::line_integer_parser::parse_line_of_integers;
 use std::collections::HashMap;
+use std::fmt;
 use std::fs;
+use std::hash::Hash;
+use std::str::FromStr;
+
+/// Integer types a [`Puzzle`] can hold. Every result is accumulated as an
+/// `i128`, so any element type that widens losslessly into it is accepted.
+pub trait Element: Copy + Ord + Hash + FromStr + Into<i128> {}
+
+impl<T> Element for T where T: Copy + Ord + Hash + FromStr + Into<i128> {}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PuzzleError {
+    Overflow,
+    ColumnOutOfRange(usize),
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleError::Overflow => write!(f, "arithmetic overflow"),
+            PuzzleError::ColumnOutOfRange(index) => write!(f, "no column #{}", index + 1),
+        }
+    }
+}
 
 #[derive(Debug)]
-pub struct Puzzle {
-    left: Vec<i32>,
-    right: Vec<i32>,
+pub struct Puzzle<T: Element> {
+    labels: Vec<String>,
+    columns: Vec<Vec<T>>,
 }
 
-impl Puzzle {
-    pub fn from_file(filename: &str) -> Puzzle {
-        let (left, right): (Vec<_>, Vec<_>) = fs::read_to_string(filename)
-            .expect("Error reading file")
+#[derive(Debug, PartialEq, Eq)]
+pub struct ComparisonMatrix {
+    pub labels: Vec<String>,
+    pub distance: Vec<Vec<i128>>,
+    pub similarity: Vec<Vec<i128>>,
+}
+
+impl<T: Element> Puzzle<T> {
+    pub fn from_file(filename: &str) -> Puzzle<T> {
+        let contents = fs::read_to_string(filename).expect("Error reading file");
+        Self::from_string(&contents)
+    }
+
+    /// Reads whitespace separated columns, one row per line. A first line
+    /// without any numeric token is taken as the column labels.
+    pub fn from_string(input: &str) -> Puzzle<T> {
+        let mut lines = input
             .lines()
-            .map(|line| parse_two_integers(line).unwrap().1)
-            .unzip();
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .peekable();
+        let labels: Option<Vec<String>> = lines
+            .next_if(|line| !line.split_whitespace().any(Self::is_numeric))
+            .map(|line| line.split_whitespace().map(String::from).collect());
+        let mut columns: Vec<Vec<T>> = vec![];
+
+        for line in lines {
+            let row = Self::parse_row(line).unwrap_or_else(|| panic!("Invalid row: {}", line));
 
-        Self { left, right }
+            if columns.is_empty() {
+                columns = vec![vec![]; row.len()];
+            }
+            if row.len() != columns.len() {
+                panic!("Expected {} columns in row: {}", columns.len(), line);
+            }
+
+            for (column, value) in columns.iter_mut().zip(row) {
+                column.push(value);
+            }
+        }
+
+        match labels {
+            Some(labels) => Self::with_labels(labels, columns),
+            None => Self::from_columns(columns),
+        }
     }
 
     #[allow(dead_code)]
-    pub fn new(left: Vec<i32>, right: Vec<i32>) -> Puzzle {
-        Self { left, right }
+    pub fn new(left: Vec<T>, right: Vec<T>) -> Puzzle<T> {
+        Self::from_columns(vec![left, right])
+    }
+
+    pub fn from_columns(columns: Vec<Vec<T>>) -> Puzzle<T> {
+        let labels = (1..=columns.len())
+            .map(|index| format!("#{}", index))
+            .collect();
+
+        Self::with_labels(labels, columns)
+    }
+
+    pub fn with_labels(labels: Vec<String>, columns: Vec<Vec<T>>) -> Puzzle<T> {
+        if !columns.is_empty() && labels.len() != columns.len() {
+            panic!("Expected {} labels, found {}", columns.len(), labels.len());
+        }
+
+        Self { labels, columns }
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn calculate_distance(&self) -> Result<i128, PuzzleError> {
+        self.distance(0, 1)
+    }
+
+    pub fn calculate_frequency(&self) -> Result<i128, PuzzleError> {
+        self.similarity(0, 1)
+    }
+
+    pub fn distance(&self, left: usize, right: usize) -> Result<i128, PuzzleError> {
+        Self::sorted_distance(
+            &Self::sorted(self.column(left)?),
+            &Self::sorted(self.column(right)?),
+        )
+    }
+
+    pub fn similarity(&self, left: usize, right: usize) -> Result<i128, PuzzleError> {
+        Self::frequency_similarity(
+            &Self::build_frequency_map(self.column(left)?),
+            &Self::build_frequency_map(self.column(right)?),
+        )
     }
 
-    pub fn calculate_distance(&self) -> i32 {
-        let mut sorted_left = self.left.to_vec();
-        let mut sorted_right = self.right.to_vec();
+    fn column(&self, index: usize) -> Result<&[T], PuzzleError> {
+        self.columns
+            .get(index)
+            .map(Vec::as_slice)
+            .ok_or(PuzzleError::ColumnOutOfRange(index))
+    }
+
+    /// Distance and similarity for every pair of columns. Both matrices are
+    /// symmetric, so swapping two columns gives the same values.
+    pub fn comparison_matrix(&self) -> Result<ComparisonMatrix, PuzzleError> {
+        let sorted: Vec<Vec<T>> = self.columns.iter().map(|c| Self::sorted(c)).collect();
+        let frequencies: Vec<HashMap<T, i128>> = self
+            .columns
+            .iter()
+            .map(|c| Self::build_frequency_map(c))
+            .collect();
+
+        let distance = sorted
+            .iter()
+            .map(|left| {
+                sorted
+                    .iter()
+                    .map(|right| Self::sorted_distance(left, right))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let similarity = frequencies
+            .iter()
+            .map(|left| {
+                frequencies
+                    .iter()
+                    .map(|right| Self::frequency_similarity(left, right))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
 
-        sorted_left.sort();
-        sorted_right.sort();
+        Ok(ComparisonMatrix {
+            labels: self.labels.clone(),
+            distance,
+            similarity,
+        })
+    }
+
+    fn is_numeric(token: &str) -> bool {
+        let digits = token.strip_prefix(['+', '-']).unwrap_or(token);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    }
+
+    fn parse_row(line: &str) -> Option<Vec<T>> {
+        match parse_line_of_integers::<T>(line) {
+            Ok(("", row)) if !row.is_empty() => Some(row),
+            _ => None,
+        }
+    }
+
+    fn sorted(numbers: &[T]) -> Vec<T> {
+        let mut sorted = numbers.to_vec();
+        sorted.sort();
+        sorted
+    }
 
+    fn sorted_distance(sorted_left: &[T], sorted_right: &[T]) -> Result<i128, PuzzleError> {
         sorted_left
             .iter()
             .zip(sorted_right.iter())
-            .map(|(a, b)| (a - b).abs())
-            .sum()
+            .try_fold(0i128, |total, (&a, &b)| {
+                let distance = a.into().checked_sub(b.into())?.checked_abs()?;
+                total.checked_add(distance)
+            })
+            .ok_or(PuzzleError::Overflow)
     }
 
-    pub fn calculate_frequency(&self) -> i32 {
-        let left_frequency_map = Self::build_frequency_map(&self.left);
-        let right_frequency_map = Self::build_frequency_map(&self.right);
-
+    fn frequency_similarity(
+        left_frequency_map: &HashMap<T, i128>,
+        right_frequency_map: &HashMap<T, i128>,
+    ) -> Result<i128, PuzzleError> {
         left_frequency_map
             .iter()
-            .map(|(number, left_freq)| {
-                let right_freq = right_frequency_map.get(number).unwrap_or(&0);
-                number * left_freq * right_freq
+            .try_fold(0i128, |total, (&number, &left_freq)| {
+                let right_freq = *right_frequency_map.get(&number).unwrap_or(&0);
+                let score = number
+                    .into()
+                    .checked_mul(left_freq)?
+                    .checked_mul(right_freq)?;
+                total.checked_add(score)
             })
-            .sum()
+            .ok_or(PuzzleError::Overflow)
     }
 
-    fn build_frequency_map(numbers: &[i32]) -> HashMap<i32, i32> {
+    fn build_frequency_map(numbers: &[T]) -> HashMap<T, i128> {
         let mut frequency = HashMap::new();
 
         for &number in numbers {
@@ -62,9 +227,52 @@ impl Puzzle {
     }
 }
 
+impl fmt::Display for ComparisonMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_table(f, "Distance", &self.distance)?;
+        writeln!(f)?;
+        self.write_table(f, "Similarity", &self.similarity)
+    }
+}
+
+impl ComparisonMatrix {
+    fn write_table(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        title: &str,
+        values: &[Vec<i128>],
+    ) -> fmt::Result {
+        let width = self
+            .labels
+            .iter()
+            .map(|label| label.len())
+            .chain(values.iter().flatten().map(|value| value.to_string().len()))
+            .chain(std::iter::once(title.len()))
+            .max()
+            .unwrap_or(0);
+
+        write!(f, "{:<width$}", title)?;
+        for label in &self.labels {
+            write!(f, " {:>width$}", label)?;
+        }
+        writeln!(f)?;
+
+        for (label, row) in self.labels.iter().zip(values) {
+            write!(f, "{:<width$}", label)?;
+            for value in row {
+                write!(f, " {:>width$}", value)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use test_case::test_case;
 
     #[test]
     fn test_calculate_distance() {
@@ -75,7 +283,7 @@ mod tests {
         // Act
         let result = puzzle.calculate_distance();
         // Assert
-        assert_eq!(result, 11);
+        assert_eq!(result, Ok(11));
     }
 
     #[test]
@@ -87,13 +295,61 @@ mod tests {
         // Act
         let result = puzzle.calculate_frequency();
         // Assert
-        assert_eq!(result, 31);
+        assert_eq!(result, Ok(31));
+    }
+
+    #[test]
+    fn test_calculate_frequency_exceeds_element_type() {
+        // Arrange
+        let left = vec![u64::MAX, u64::MAX];
+        let right = vec![u64::MAX, u64::MAX, u64::MAX];
+        let puzzle = Puzzle::new(left, right);
+        // Act
+        let result = puzzle.calculate_frequency();
+        // Assert
+        assert_eq!(result, Ok(u64::MAX as i128 * 2 * 3));
+    }
+
+    #[test]
+    fn test_calculate_distance_exceeds_element_type() {
+        // Arrange
+        let left: Vec<i64> = vec![i64::MIN, i64::MIN];
+        let right: Vec<i64> = vec![i64::MAX, i64::MAX];
+        let puzzle = Puzzle::new(left, right);
+        // Act
+        let result = puzzle.calculate_distance();
+        // Assert
+        assert_eq!(result, Ok((i64::MAX as i128 - i64::MIN as i128) * 2));
+    }
+
+    #[test]
+    fn test_calculate_distance_overflow() {
+        // Arrange
+        let left = vec![i128::MIN];
+        let right = vec![i128::MAX];
+        let puzzle = Puzzle::new(left, right);
+        // Act
+        let result = puzzle.calculate_distance();
+        // Assert
+        assert_eq!(result, Err(PuzzleError::Overflow));
+    }
+
+    #[test]
+    fn test_calculate_frequency_overflow() {
+        // Arrange
+        let left = vec![i128::MAX, i128::MAX];
+        let right = vec![i128::MAX];
+        let puzzle = Puzzle::new(left, right);
+        // Act
+        let result = puzzle.calculate_frequency();
+        // Assert
+        assert_eq!(result, Err(PuzzleError::Overflow));
     }
 
     #[test]
     fn test_build_frequency_map_duplicates() {
         // Arrange
-        let numbers = vec![5, 5, 5, 2, 2, 3];
+        let numbers: Vec<i64> = vec![5, 5, 5, 2, 2, 3];
         // Act
         let result = Puzzle::build_frequency_map(&numbers);
         // Assert
@@ -102,4 +358,92 @@ mod tests {
         assert_eq!(result.get(&3), Some(&1));
         assert_eq!(result.get(&4), None);
     }
+
+    #[test]
+    fn test_from_string_multiple_columns() {
+        // Arrange
+        let input = "3 4 1\n4 3 1\n2 5 2\n";
+        // Act
+        let puzzle = Puzzle::<i64>::from_string(input);
+        // Assert
+        assert_eq!(puzzle.column_count(), 3);
+        assert_eq!(puzzle.labels, vec!["#1", "#2", "#3"]);
+        assert_eq!(puzzle.columns[2], vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn test_from_string_with_header() {
+        // Arrange
+        let input = "north south\n3 4\n4 3\n";
+        // Act
+        let puzzle = Puzzle::<u64>::from_string(input);
+        // Assert
+        assert_eq!(puzzle.labels, vec!["north", "south"]);
+        assert_eq!(puzzle.columns, vec![vec![3, 4], vec![4, 3]]);
+    }
+
+    #[test]
+    fn test_from_string_negative_values() {
+        // Arrange
+        let input = "-3   4\n4   -3\n-170141183460469231731687303715884105728   0\n";
+        // Act
+        let puzzle = Puzzle::<i128>::from_string(input);
+        // Assert
+        assert_eq!(puzzle.columns, vec![vec![-3, 4, i128::MIN], vec![4, -3, 0]]);
+        assert_eq!(puzzle.calculate_frequency(), Ok(-3 + 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid row: 3 x")]
+    fn test_from_string_malformed_first_row() {
+        // Arrange
+        let input = "3 x\n4 3\n";
+        // Act
+        Puzzle::<i64>::from_string(input);
+    }
+
+    #[test_case(""; "empty")]
+    #[test_case("north south\n"; "header only")]
+    #[test_case("3\n4\n"; "single column")]
+    fn test_calculate_missing_column(input: &str) {
+        // Arrange
+        let puzzle = Puzzle::<i64>::from_string(input);
+        let missing = PuzzleError::ColumnOutOfRange(puzzle.column_count());
+        // Act
+        let distance = puzzle.calculate_distance();
+        let frequency = puzzle.calculate_frequency();
+        // Assert
+        assert_eq!(distance, Err(missing.clone()));
+        assert_eq!(frequency, Err(missing));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected 2 columns")]
+    fn test_from_string_inconsistent_columns() {
+        // Arrange
+        let input = "3 4\n4 3 1\n";
+        // Act
+        Puzzle::<i64>::from_string(input);
+    }
+
+    #[test]
+    fn test_comparison_matrix() {
+        // Arrange
+        let puzzle = Puzzle::<i64>::from_columns(vec![
+            vec![3, 4, 2, 1, 3, 3],
+            vec![4, 3, 5, 3, 9, 3],
+            vec![3, 4, 2, 1, 3, 3],
+        ]);
+        // Act
+        let matrix = puzzle.comparison_matrix().unwrap();
+        // Assert
+        assert_eq!(
+            matrix.distance,
+            vec![vec![0, 11, 0], vec![11, 0, 11], vec![0, 11, 0]]
+        );
+        assert_eq!(
+            matrix.similarity,
+            vec![vec![34, 31, 34], vec![31, 45, 31], vec![34, 31, 34]]
+        );
+    }
 }
@@ -1,33 +1,148 @@
+mod policy;
 mod report;
 
-use crate::day02::report::Report;
+use crate::day02::policy::SafetyPolicy;
+use crate::day02::report::{Report, Verdict, Violation, ViolationKind};
+use std::collections::BTreeMap;
 use std::fs;
 
-pub(crate) fn run() {
+pub(crate) fn run(args: &[String]) {
     let filename = "data/day02/input.txt";
-    let reports = parse_file_data(filename);
+    let reports = match parse_file_data(filename) {
+        Ok(reports) => reports,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return;
+        }
+    };
 
-    let result_part_1 = calculate_valid_reports(&reports);
-    println!("Result of Day 02, Part 1 is {}", result_part_1);
+    let policy = match SafetyPolicy::from_args(args) {
+        Ok(policy) => policy,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return;
+        }
+    };
+
+    if args.iter().any(|arg| arg == "--diagnose") {
+        print_diagnostics(&reports, &policy);
+    }
+
+    if policy != SafetyPolicy::default() {
+        let result = calculate_reports_with_policy(&reports, &policy);
+        println!("Result of Day 02 with custom policy is {}", result);
+    }
+
+    let statistics = calculate_valid_reports(&reports);
+    println!("Result of Day 02, Part 1 is {}", statistics.safe);
+    if args.iter().any(|arg| arg == "--stats") {
+        print_statistics(&statistics);
+    }
     let result_part_2 = calculate_valid_safe_reports(&reports);
     println!("Result of Day 02, Part 2 is {}", result_part_2);
 }
 
-fn parse_file_data(filename: &str) -> Vec<Report> {
-    fs::read_to_string(filename)
-        .expect("Error reading file")
+#[derive(Debug, PartialEq, Eq, Default)]
+struct ReportStatistics {
+    safe: usize,
+    failures: BTreeMap<ViolationKind, usize>,
+    longest_safe_run: usize,
+}
+
+fn parse_file_data(filename: &str) -> Result<Vec<Report>, String> {
+    parse_reports(&fs::read_to_string(filename).expect("Error reading file"))
+}
+
+/// One report per line. Blank lines are skipped rather than read as empty
+/// reports, which would count as safe.
+fn parse_reports(contents: &str) -> Result<Vec<Report>, String> {
+    contents
         .lines()
-        .map(Report::from_string)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            Report::from_string(line).map_err(|error| format!("line {}: {}", index + 1, error))
+        })
         .collect()
 }
 
-fn calculate_valid_reports(reports: &Vec<Report>) -> usize {
-    let safe_count = reports.iter().filter(|report| report.is_safe()).count();
+fn print_diagnostics(reports: &[Report], policy: &SafetyPolicy) {
+    for (index, violation) in diagnose_reports(reports, policy) {
+        let report = &reports[index];
+        let levels = report.levels();
+        print!(
+            "Report {} {:?}: levels {} and {} ({} -> {}) {}",
+            index + 1,
+            levels,
+            violation.from,
+            violation.to,
+            levels[violation.from],
+            levels[violation.to],
+            violation.kind
+        );
+        if policy.tolerance > 0 {
+            let unlimited = policy.clone().with_tolerance(levels.len());
+            if let Some(removals) = report.removals(&unlimited) {
+                print!(
+                    ", needs removals {:?} but tolerance is {}",
+                    removals, policy.tolerance
+                );
+            }
+        }
+        println!();
+    }
+}
 
-    safe_count
+/// The first violation of every report that fails `policy`, tolerance
+/// included, by report index.
+fn diagnose_reports(reports: &[Report], policy: &SafetyPolicy) -> Vec<(usize, Violation)> {
+    reports
+        .iter()
+        .enumerate()
+        .filter(|(_, report)| !report.check(policy))
+        .filter_map(|(index, report)| match report.diagnose_with(policy) {
+            Verdict::Unsafe(violation) => Some((index, violation)),
+            Verdict::Safe => None,
+        })
+        .collect()
+}
+
+fn calculate_reports_with_policy(reports: &[Report], policy: &SafetyPolicy) -> usize {
+    reports.iter().filter(|report| report.check(policy)).count()
 }
 
-fn calculate_valid_safe_reports(reports: &Vec<Report>) -> usize {
+fn print_statistics(statistics: &ReportStatistics) {
+    for (kind, count) in &statistics.failures {
+        println!("Unsafe reports by {}: {}", kind, count);
+    }
+    println!(
+        "Longest run of safe reports: {}",
+        statistics.longest_safe_run
+    );
+}
+
+fn calculate_valid_reports(reports: &[Report]) -> ReportStatistics {
+    let mut statistics = ReportStatistics::default();
+    let mut safe_run = 0;
+
+    for report in reports {
+        match report.diagnose() {
+            Verdict::Safe => {
+                statistics.safe += 1;
+                safe_run += 1;
+                statistics.longest_safe_run = statistics.longest_safe_run.max(safe_run);
+            }
+            Verdict::Unsafe(violation) => {
+                *statistics.failures.entry(violation.kind).or_insert(0) += 1;
+                safe_run = 0;
+            }
+        }
+    }
+
+    statistics
+}
+
+fn calculate_valid_safe_reports(reports: &[Report]) -> usize {
     let safe_count = reports
         .iter()
         .filter(|report| report.is_safe_with_tolerance())
@@ -56,6 +171,96 @@ mod tests {
         let result = calculate_valid_reports(&reports);
 
         // Assert
-        assert_eq!(result, 2);
+        assert_eq!(result.safe, 2);
+    }
+
+    #[test]
+    fn test_parse_reports_skips_blank_lines() {
+        // Arrange
+        let contents = "7 6 4 2 1\n\n1 2 7 8 9\n   \n\n";
+
+        // Act
+        let result = parse_reports(contents);
+
+        // Assert
+        assert_eq!(
+            result,
+            Ok(vec![
+                Report::new(vec![7, 6, 4, 2, 1]),
+                Report::new(vec![1, 2, 7, 8, 9])
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_reports_error_line() {
+        // Arrange
+        let contents = "7 6 4 2 1\n\n1 2 x\n";
+
+        // Act
+        let result = parse_reports(contents);
+
+        // Assert
+        assert!(result.unwrap_err().starts_with("line 3:"));
+    }
+
+    #[test]
+    fn test_diagnose_reports_with_tolerance() {
+        // Arrange
+        let reports = vec![
+            Report::new(vec![7, 6, 4, 2, 1]), // Safe
+            Report::new(vec![1, 2, 7, 8, 9]), // Unsafe
+            Report::new(vec![1, 3, 2, 4, 5]), // Safe after one removal
+            Report::new(vec![8, 6, 4, 4, 1]), // Safe after one removal
+        ];
+        let policy = SafetyPolicy::default().with_tolerance(1);
+
+        // Act
+        let result = diagnose_reports(&reports, &policy);
+
+        // Assert
+        assert_eq!(
+            result,
+            vec![(
+                1,
+                Violation {
+                    from: 1,
+                    to: 2,
+                    kind: ViolationKind::StepTooLarge
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_report_statistics() {
+        // Arrange
+        let reports = vec![
+            Report::new(vec![7, 6, 4, 2, 1]), // Safe
+            Report::new(vec![1, 2, 7, 8, 9]), // Step too large
+            Report::new(vec![]),              // Safe
+            Report::new(vec![4]),             // Safe
+            Report::new(vec![1, 3, 6, 7, 9]), // Safe
+            Report::new(vec![1, 3, 2, 4, 5]), // Direction change
+            Report::new(vec![8, 6, 4, 4, 1]), // Zero step
+            Report::new(vec![9, 7, 6, 2, 1]), // Step too large
+        ];
+
+        // Act
+        let result = calculate_valid_reports(&reports);
+
+        // Assert
+        assert_eq!(
+            result,
+            ReportStatistics {
+                safe: 4,
+                failures: BTreeMap::from([
+                    (ViolationKind::DirectionChange, 1),
+                    (ViolationKind::ZeroStep, 1),
+                    (ViolationKind::StepTooLarge, 2),
+                ]),
+                longest_safe_run: 3,
+            }
+        );
     }
 }
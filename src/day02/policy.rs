@@ -0,0 +1,197 @@
+use crate::day02::report::ViolationKind;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AllowedDirection {
+    Any,
+    Increasing,
+    Decreasing,
+}
+
+/// Rule a report is checked against. The default is the puzzle rule: strictly
+/// monotonic in either direction with steps of 1 to 3 and no removals.
+///
+/// With non-strict monotonicity a repeated level is accepted regardless of
+/// `min_step`, and the direction is set by the first step that is not zero.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SafetyPolicy {
+    pub min_step: i32,
+    pub max_step: i32,
+    pub strict: bool,
+    pub direction: AllowedDirection,
+    pub tolerance: usize,
+}
+
+impl Default for SafetyPolicy {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            strict: true,
+            direction: AllowedDirection::Any,
+            tolerance: 0,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    pub fn with_tolerance(mut self, tolerance: usize) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Builds a policy from `--min-step N`, `--max-step N`, `--non-strict`,
+    /// `--direction any|increasing|decreasing` and `--tolerance N`. Other
+    /// arguments are left for the caller.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut policy = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))
+            };
+
+            match arg.as_str() {
+                "--min-step" => policy.min_step = parse_number(arg, value()?)?,
+                "--max-step" => policy.max_step = parse_number(arg, value()?)?,
+                "--tolerance" => policy.tolerance = parse_number(arg, value()?)?,
+                "--non-strict" => policy.strict = false,
+                "--direction" => {
+                    policy.direction = match value()?.as_str() {
+                        "any" => AllowedDirection::Any,
+                        "increasing" => AllowedDirection::Increasing,
+                        "decreasing" => AllowedDirection::Decreasing,
+                        other => return Err(format!("Invalid direction: {}", other)),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if policy.min_step < 0 || policy.min_step > policy.max_step {
+            return Err(format!(
+                "Invalid step range: {}..={}",
+                policy.min_step, policy.max_step
+            ));
+        }
+
+        Ok(policy)
+    }
+
+    pub fn allows_direction(&self, ascending: bool) -> bool {
+        match self.direction {
+            AllowedDirection::Any => true,
+            AllowedDirection::Increasing => ascending,
+            AllowedDirection::Decreasing => !ascending,
+        }
+    }
+
+    /// Checks one step of a report that is known to go in `ascending` order.
+    pub fn check_step(&self, prev: i32, current: i32, ascending: bool) -> Option<ViolationKind> {
+        let difference = current - prev;
+
+        if difference == 0 {
+            return self.strict.then_some(ViolationKind::ZeroStep);
+        }
+        if (difference > 0) != ascending {
+            return Some(ViolationKind::DirectionChange);
+        }
+
+        match difference.abs() {
+            step if step < self.min_step => Some(ViolationKind::StepTooSmall),
+            step if step > self.max_step => Some(ViolationKind::StepTooLarge),
+            _ => None,
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value for {}: {}", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::args;
+    use test_case::test_case;
+
+    #[test]
+    fn test_policy_from_no_args() {
+        // Arrange
+        let input = args("--diagnose");
+        // Act
+        let policy = SafetyPolicy::from_args(&input);
+        // Assert
+        assert_eq!(policy, Ok(SafetyPolicy::default()));
+    }
+
+    #[test]
+    fn test_policy_from_args() {
+        // Arrange
+        let input =
+            args("--min-step 2 --max-step 5 --non-strict --direction decreasing --tolerance 2");
+        // Act
+        let policy = SafetyPolicy::from_args(&input);
+        // Assert
+        assert_eq!(
+            policy,
+            Ok(SafetyPolicy {
+                min_step: 2,
+                max_step: 5,
+                strict: false,
+                direction: AllowedDirection::Decreasing,
+                tolerance: 2,
+            })
+        );
+    }
+
+    #[test_case("--min-step"; "missing value")]
+    #[test_case("--max-step x"; "not a number")]
+    #[test_case("--direction sideways"; "unknown direction")]
+    #[test_case("--min-step 4 --max-step 2"; "empty range")]
+    fn test_policy_from_invalid_args(input: &str) {
+        // Arrange
+        let input = args(input);
+        // Act
+        let policy = SafetyPolicy::from_args(&input);
+        // Assert
+        assert!(policy.is_err());
+    }
+
+    #[test_case(1, 1, true, Some(ViolationKind::ZeroStep))]
+    #[test_case(1, 2, false, Some(ViolationKind::DirectionChange))]
+    #[test_case(1, 5, true, Some(ViolationKind::StepTooLarge))]
+    #[test_case(5, 2, false, None)]
+    fn test_default_policy_check_step(
+        prev: i32,
+        current: i32,
+        ascending: bool,
+        expected: Option<ViolationKind>,
+    ) {
+        // Arrange
+        let policy = SafetyPolicy::default();
+        // Act
+        let result = policy.check_step(prev, current, ascending);
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_non_strict_policy_accepts_repeated_level() {
+        // Arrange
+        let policy = SafetyPolicy {
+            min_step: 2,
+            strict: false,
+            ..SafetyPolicy::default()
+        };
+        // Act
+        let repeated = policy.check_step(4, 4, true);
+        let small = policy.check_step(4, 5, true);
+        // Assert
+        assert_eq!(repeated, None);
+        assert_eq!(small, Some(ViolationKind::StepTooSmall));
+    }
+}
@@ -1,72 +1,264 @@
+use crate::day02::policy::SafetyPolicy;
 use crate::utils::line_integer_parser::parse_line_of_integers;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
 
 #[derive(PartialEq, Debug)]
 pub struct Report {
     data: Vec<i32>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseReportError {
+    input: String,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum ViolationKind {
+    WrongDirection,
+    DirectionChange,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+}
+
+/// First pair of adjacent levels, by index, that breaks the safety policy.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Violation {
+    pub from: usize,
+    pub to: usize,
+    pub kind: ViolationKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Verdict {
+    Safe,
+    Unsafe(Violation),
+}
+
 impl Report {
+    #[allow(dead_code)]
     pub fn new(data: Vec<i32>) -> Report {
         Self { data }
     }
 
-    pub fn from_string(input: &str) -> Report {
-        match parse_line_of_integers(input) {
-            Ok((_, data)) => Self { data },
-            Err(_) => panic!("Error parsing data"),
+    /// Parses whitespace separated, non-negative levels. A blank line is an
+    /// empty report; reports with fewer than two levels are always safe.
+    pub fn from_string(input: &str) -> Result<Report, ParseReportError> {
+        let error = || ParseReportError {
+            input: input.to_string(),
+        };
+
+        match parse_line_of_integers::<u32>(input.trim()) {
+            Ok(("", levels)) => levels
+                .into_iter()
+                .map(i32::try_from)
+                .collect::<Result<_, _>>()
+                .map(|data| Self { data })
+                .map_err(|_| error()),
+            _ => Err(error()),
         }
     }
 
+    pub fn levels(&self) -> &[i32] {
+        &self.data
+    }
+
+    #[allow(dead_code)]
     pub fn is_safe(&self) -> bool {
-        let mut prev = self.data[0];
-        let is_ascending = self.data[1] > prev;
-
-        for &current in &self.data[1..] {
-            let difference = (current - prev).abs();
-
-            if (is_ascending && current < prev)
-                || (!is_ascending && current > prev)
-                || difference < 1
-                || difference > 3
-            {
-                return false;
+        self.check(&SafetyPolicy::default())
+    }
+
+    pub fn is_safe_with_tolerance(&self) -> bool {
+        self.check(&SafetyPolicy::default().with_tolerance(1))
+    }
+
+    pub fn check(&self, policy: &SafetyPolicy) -> bool {
+        if policy.tolerance == 0 {
+            self.diagnose_with(policy) == Verdict::Safe
+        } else {
+            self.removals(policy).is_some()
+        }
+    }
+
+    pub fn diagnose(&self) -> Verdict {
+        self.diagnose_with(&SafetyPolicy::default())
+    }
+
+    /// Reports the first violation of `policy` ignoring its tolerance.
+    pub fn diagnose_with(&self, policy: &SafetyPolicy) -> Verdict {
+        let mut ascending = None;
+
+        for (from, pair) in self.data.windows(2).enumerate() {
+            let (prev, current) = (pair[0], pair[1]);
+
+            if ascending.is_none() && current != prev {
+                ascending = Some(current > prev);
+                if !policy.allows_direction(current > prev) {
+                    return Self::unsafe_at(from, ViolationKind::WrongDirection);
+                }
             }
 
-            prev = current;
+            let direction = ascending.unwrap_or(false);
+            if let Some(kind) = policy.check_step(prev, current, direction) {
+                return Self::unsafe_at(from, kind);
+            }
         }
 
-        true
+        Verdict::Safe
     }
 
-    pub fn is_safe_with_tolerance(&self) -> bool {
-        if self.is_safe() {
-            return true;
+    #[allow(dead_code)]
+    pub fn safe_after_removals(&self, max_removals: usize) -> Option<Vec<usize>> {
+        self.removals(&SafetyPolicy::default().with_tolerance(max_removals))
+    }
+
+    /// Returns the indices to remove so the report satisfies `policy`, or
+    /// `None` if that takes more than its tolerance. The tolerance does not
+    /// affect the running time, which is O(n * s) for `s` allowed step
+    /// sizes, so linear for a fixed step range such as the puzzle's 1 to 3.
+    pub fn removals(&self, policy: &SafetyPolicy) -> Option<Vec<usize>> {
+        [true, false]
+            .into_iter()
+            .filter(|&ascending| policy.allows_direction(ascending))
+            .filter_map(|ascending| self.removals_for_direction(policy, ascending))
+            .min_by_key(|removed| removed.len())
+    }
+
+    fn unsafe_at(from: usize, kind: ViolationKind) -> Verdict {
+        Verdict::Unsafe(Violation {
+            from,
+            to: from + 1,
+            kind,
+        })
+    }
+
+    /// Keeps the longest run of levels whose steps all satisfy `policy`.
+    /// Instead of scanning every earlier level, each level only looks up the
+    /// best run ending at each value one allowed step away.
+    fn removals_for_direction(&self, policy: &SafetyPolicy, ascending: bool) -> Option<Vec<usize>> {
+        let size = self.data.len();
+        if size == 0 {
+            return Some(vec![]);
         }
 
-        for i in 0..self.data.len() {
-            let mut modified_data = self.data.clone();
-            modified_data.remove(i);
-            let modified_report = Report::new(modified_data);
-            if modified_report.is_safe() {
-                return true;
+        let sign = if ascending { 1 } else { -1 };
+        let smallest = policy.min_step.max(1);
+        let differences = (smallest..=policy.max_step)
+            .map(|step| step * sign)
+            .chain((!policy.strict).then_some(0));
+        let difference_count = differences.clone().count();
+
+        // length[i]: most levels kept among 0..=i when level i is kept last,
+        // and previous[i] the kept level before it. Ties keep the earliest.
+        let mut length = vec![1; size];
+        let mut previous: Vec<Option<usize>> = vec![None; size];
+        let mut best_by_value: HashMap<i32, usize> = HashMap::new();
+
+        for current in 0..size {
+            let value = self.data[current];
+            let before = if difference_count <= current {
+                differences
+                    .clone()
+                    .filter_map(|difference| value.checked_sub(difference))
+                    .filter_map(|before| best_by_value.get(&before).copied())
+                    .max_by_key(|&before| (length[before], Reverse(before)))
+            } else {
+                (0..current)
+                    .filter(|&before| {
+                        policy
+                            .check_step(self.data[before], value, ascending)
+                            .is_none()
+                    })
+                    .max_by_key(|&before| (length[before], Reverse(before)))
+            };
+
+            if let Some(before) = before {
+                length[current] = length[before] + 1;
+                previous[current] = Some(before);
+            }
+            let best = best_by_value.entry(value).or_insert(current);
+            if length[current] > length[*best] {
+                *best = current;
             }
         }
 
-        false
+        let last = (0..size).max_by_key(|&index| (length[index], Reverse(index)))?;
+        if size - length[last] > policy.tolerance {
+            return None;
+        }
+
+        let mut kept = vec![false; size];
+        let mut index = Some(last);
+        while let Some(current) = index {
+            kept[current] = true;
+            index = previous[current];
+        }
+
+        Some((0..size).filter(|&index| !kept[index]).collect())
+    }
+}
+
+impl fmt::Display for ParseReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid report: {:?}", self.input)
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViolationKind::WrongDirection => write!(f, "direction not allowed"),
+            ViolationKind::DirectionChange => write!(f, "direction change"),
+            ViolationKind::ZeroStep => write!(f, "zero step"),
+            ViolationKind::StepTooSmall => write!(f, "step too small"),
+            ViolationKind::StepTooLarge => write!(f, "step too large"),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::day02::policy::AllowedDirection;
     use test_case::test_case;
 
+    #[test_case("7 6 4 2 1", vec![7, 6, 4, 2, 1])]
+    #[test_case("  1 3  6 ", vec![1, 3, 6])]
+    #[test_case("42", vec![42])]
+    #[test_case("", vec![])]
+    fn test_report_from_string(input: &str, expected: Vec<i32>) {
+        // Arrange
+        // Act
+        let result = Report::from_string(input);
+
+        // Assert
+        assert_eq!(result, Ok(Report::new(expected)));
+    }
+
+    #[test_case("1 2 x")]
+    #[test_case("1,2,3")]
+    #[test_case("-1 2")]
+    #[test_case("99999999999 1")]
+    #[test_case("3000000000 1")]
+    fn test_report_from_invalid_string(input: &str) {
+        // Arrange
+        // Act
+        let result = Report::from_string(input);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
     #[test_case(vec![7, 6, 4, 2, 1], true)]
     #[test_case(vec![1, 2, 7, 8, 9], false)]
     #[test_case(vec![9, 7, 6, 2, 1], false)]
     #[test_case(vec![1, 3, 2, 4, 5], false)]
     #[test_case(vec![8, 6, 4, 4, 1], false)]
     #[test_case(vec![1, 3, 6, 7, 9], true)]
+    #[test_case(vec![5], true)]
+    #[test_case(vec![], true)]
     fn test_report_is_safe(data: Vec<i32>, expected: bool) {
         // Arrange
         let report = Report::new(data);
@@ -84,6 +276,9 @@ mod tests {
     #[test_case(vec![1, 3, 2, 4, 5], true)]
     #[test_case(vec![8, 6, 4, 4, 1], true)]
     #[test_case(vec![1, 3, 6, 7, 9], true)]
+    #[test_case(vec![1, 9], true)]
+    #[test_case(vec![5], true)]
+    #[test_case(vec![], true)]
     fn test_report_is_safe_with_tolerance(data: Vec<i32>, expected: bool) {
         // Arrange
         let report = Report::new(data);
@@ -94,4 +289,151 @@ mod tests {
         // Assert
         assert_eq!(result, expected);
     }
+
+    #[test_case(vec![7, 6, 4, 2, 1], 0, Some(vec![]))]
+    #[test_case(vec![1, 3, 2, 4, 5], 1, Some(vec![2]))]
+    #[test_case(vec![8, 6, 4, 4, 1], 1, Some(vec![3]))]
+    #[test_case(vec![1, 2, 7, 8, 9], 1, None)]
+    #[test_case(vec![1, 2, 7, 8, 9], 2, Some(vec![0, 1]))]
+    #[test_case(vec![9, 1, 2, 3, 9, 4], 2, Some(vec![0, 4]))]
+    #[test_case(vec![5, 1, 2, 3, 4], 1, Some(vec![0]))]
+    #[test_case(vec![1, 2, 3, 4, 0], 1, Some(vec![4]))]
+    fn test_report_safe_after_removals(data: Vec<i32>, k: usize, expected: Option<Vec<usize>>) {
+        // Arrange
+        let report = Report::new(data);
+
+        // Act
+        let result = report.safe_after_removals(k);
+
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_report_safe_after_removals_matches_brute_force() {
+        // Arrange
+        fn brute_force(data: &[i32], k: usize) -> Option<usize> {
+            (0..1usize << data.len())
+                .filter(|mask| mask.count_ones() as usize <= k)
+                .filter(|mask| {
+                    let kept: Vec<i32> = (0..data.len())
+                        .filter(|index| mask & (1 << index) == 0)
+                        .map(|index| data[index])
+                        .collect();
+                    kept.len() < 2 || Report::new(kept).is_safe()
+                })
+                .map(|mask| mask.count_ones() as usize)
+                .min()
+        }
+
+        for seed in 0..6u32.pow(5) {
+            let data: Vec<i32> = (0..5)
+                .map(|digit| (seed / 6u32.pow(digit) % 6) as i32)
+                .collect();
+            let report = Report::new(data.clone());
+
+            for k in [0, 1, 2, data.len()] {
+                // Act
+                let result = report.safe_after_removals(k);
+
+                // Assert
+                assert_eq!(result.map(|removed| removed.len()), brute_force(&data, k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_report_removals_long_report_any_tolerance() {
+        // Arrange
+        let data: Vec<i32> = (0..20_000)
+            .map(|index| if index % 2 == 0 { index } else { -1 })
+            .collect();
+        let report = Report::new(data);
+
+        // Act
+        let result = report.safe_after_removals(report.levels().len());
+
+        // Assert
+        let expected: Vec<usize> = (1..20_000).step_by(2).collect();
+        assert_eq!(result, Some(expected));
+    }
+
+    #[test_case(vec![7, 6, 4, 2, 1], Verdict::Safe)]
+    #[test_case(vec![1, 2, 7, 8, 9], Report::unsafe_at(1, ViolationKind::StepTooLarge))]
+    #[test_case(vec![9, 7, 6, 2, 1], Report::unsafe_at(2, ViolationKind::StepTooLarge))]
+    #[test_case(vec![1, 3, 2, 4, 5], Report::unsafe_at(1, ViolationKind::DirectionChange))]
+    #[test_case(vec![8, 6, 4, 4, 1], Report::unsafe_at(2, ViolationKind::ZeroStep))]
+    #[test_case(vec![3, 3, 4, 5], Report::unsafe_at(0, ViolationKind::ZeroStep))]
+    fn test_report_diagnose(data: Vec<i32>, expected: Verdict) {
+        // Arrange
+        let report = Report::new(data);
+
+        // Act
+        let result = report.diagnose();
+
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test_case(vec![1, 3, 5, 7], policy(2, 2, true, AllowedDirection::Any), Verdict::Safe)]
+    #[test_case(
+        vec![1, 2, 4],
+        policy(2, 3, true, AllowedDirection::Any),
+        Report::unsafe_at(0, ViolationKind::StepTooSmall)
+    )]
+    #[test_case(vec![1, 1, 2, 2], policy(1, 3, false, AllowedDirection::Increasing), Verdict::Safe)]
+    #[test_case(
+        vec![5, 5, 4],
+        policy(1, 3, false, AllowedDirection::Increasing),
+        Report::unsafe_at(1, ViolationKind::WrongDirection)
+    )]
+    #[test_case(
+        vec![9, 8, 9],
+        policy(1, 3, false, AllowedDirection::Decreasing),
+        Report::unsafe_at(1, ViolationKind::DirectionChange)
+    )]
+    fn test_report_diagnose_with_policy(data: Vec<i32>, policy: SafetyPolicy, expected: Verdict) {
+        // Arrange
+        let report = Report::new(data);
+
+        // Act
+        let result = report.diagnose_with(&policy);
+
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test_case(vec![1, 2, 2, 3, 9], 1, Some(vec![4]))]
+    #[test_case(vec![3, 2, 1], 2, Some(vec![1, 2]))]
+    #[test_case(vec![3, 2, 1], 1, None)]
+    fn test_report_removals_with_policy(
+        data: Vec<i32>,
+        tolerance: usize,
+        expected: Option<Vec<usize>>,
+    ) {
+        // Arrange
+        let report = Report::new(data);
+        let policy = policy(1, 3, false, AllowedDirection::Increasing).with_tolerance(tolerance);
+
+        // Act
+        let result = report.removals(&policy);
+
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    fn policy(
+        min_step: i32,
+        max_step: i32,
+        strict: bool,
+        direction: AllowedDirection,
+    ) -> SafetyPolicy {
+        SafetyPolicy {
+            min_step,
+            max_step,
+            strict,
+            direction,
+            tolerance: 0,
+        }
+    }
 }
@@ -0,0 +1,417 @@
+use std::fmt;
+use std::io::{self, Read};
+
+const BUFFER_SIZE: usize = 4096;
+const MAX_ARITY: usize = 2;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Instruction {
+    Mul(i32, i32),
+    Do,
+    Dont,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Mul(left, right) => write!(f, "mul({},{})", left, right),
+            Instruction::Do => write!(f, "do()"),
+            Instruction::Dont => write!(f, "don't()"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Opcode {
+    Mul,
+    Do,
+    Dont,
+}
+
+impl Opcode {
+    const ALL: [Opcode; 3] = [Opcode::Mul, Opcode::Do, Opcode::Dont];
+
+    fn bit(&self) -> u8 {
+        1 << *self as u8
+    }
+
+    /// Every keyword ends with its opening parenthesis, and none of them
+    /// contains the first byte of a keyword past its own first byte, so a
+    /// failed match never hides another instruction starting inside it.
+    fn keyword(&self) -> &'static [u8] {
+        match self {
+            Opcode::Mul => b"mul(",
+            Opcode::Do => b"do(",
+            Opcode::Dont => b"don't(",
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Opcode::Mul => 2,
+            Opcode::Do | Opcode::Dont => 0,
+        }
+    }
+
+    fn instruction(&self, args: [i32; MAX_ARITY]) -> Instruction {
+        match self {
+            Opcode::Mul => Instruction::Mul(args[0], args[1]),
+            Opcode::Do => Instruction::Do,
+            Opcode::Dont => Instruction::Dont,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Token {
+    pub offset: usize,
+    pub length: usize,
+    pub instruction: Instruction,
+}
+
+/// `candidates` holds one bit per opcode still matching, and `count` is the
+/// number of arguments started so far, so the state never allocates.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum State {
+    Idle,
+    Keyword {
+        matched: usize,
+        candidates: u8,
+    },
+    Arguments {
+        opcode: Opcode,
+        args: [Option<i32>; MAX_ARITY],
+        count: usize,
+        digits: usize,
+    },
+}
+
+/// Recognises instructions one byte at a time, so the input never has to be
+/// held in memory or be valid UTF-8. Arguments are unsigned decimal numbers;
+/// an instruction whose argument does not fit in an `i32` is dropped.
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    offset: usize,
+    start: usize,
+    state: State,
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self {
+            offset: 0,
+            start: 0,
+            state: State::Idle,
+        }
+    }
+
+    pub fn feed(&mut self, byte: u8) -> Option<Token> {
+        let token = self.step(byte);
+        self.offset += 1;
+        token
+    }
+
+    fn step(&mut self, byte: u8) -> Option<Token> {
+        match std::mem::replace(&mut self.state, State::Idle) {
+            State::Idle => self.start_keyword(byte),
+            State::Keyword {
+                matched,
+                candidates,
+            } => self.match_keyword(byte, matched, candidates),
+            State::Arguments {
+                opcode,
+                args,
+                count,
+                digits,
+            } => return self.match_arguments(byte, opcode, args, count, digits),
+        }
+
+        None
+    }
+
+    fn start_keyword(&mut self, byte: u8) {
+        self.start = self.offset;
+        let all = Opcode::ALL
+            .iter()
+            .fold(0, |mask, opcode| mask | opcode.bit());
+        self.match_keyword(byte, 0, all);
+    }
+
+    fn match_keyword(&mut self, byte: u8, matched: usize, candidates: u8) {
+        let candidates = Opcode::ALL
+            .iter()
+            .filter(|opcode| candidates & opcode.bit() != 0)
+            .filter(|opcode| opcode.keyword().get(matched) == Some(&byte))
+            .fold(0, |mask, opcode| mask | opcode.bit());
+
+        if candidates == 0 {
+            if matched > 0 {
+                self.start_keyword(byte);
+            }
+            return;
+        }
+
+        let matched = matched + 1;
+        self.state = match Opcode::ALL
+            .iter()
+            .find(|opcode| candidates & opcode.bit() != 0 && opcode.keyword().len() == matched)
+        {
+            Some(&opcode) => State::Arguments {
+                opcode,
+                args: [Some(0); MAX_ARITY],
+                count: opcode.arity().min(1),
+                digits: 0,
+            },
+            None => State::Keyword {
+                matched,
+                candidates,
+            },
+        };
+    }
+
+    fn match_arguments(
+        &mut self,
+        byte: u8,
+        opcode: Opcode,
+        mut args: [Option<i32>; MAX_ARITY],
+        count: usize,
+        digits: usize,
+    ) -> Option<Token> {
+        match byte {
+            b'0'..=b'9' if count > 0 => {
+                let last = &mut args[count - 1];
+                *last = last
+                    .and_then(|value| value.checked_mul(10))
+                    .and_then(|value| value.checked_add((byte - b'0') as i32));
+                self.state = State::Arguments {
+                    opcode,
+                    args,
+                    count,
+                    digits: digits + 1,
+                };
+            }
+            b',' if digits > 0 && count < opcode.arity() => {
+                self.state = State::Arguments {
+                    opcode,
+                    args,
+                    count: count + 1,
+                    digits: 0,
+                };
+            }
+            b')' if (count == 0 || digits > 0) && count == opcode.arity() => {
+                let mut values = [0; MAX_ARITY];
+                for (value, arg) in values.iter_mut().zip(&args[..count]) {
+                    *value = (*arg)?;
+                }
+                return Some(Token {
+                    offset: self.start,
+                    length: self.offset + 1 - self.start,
+                    instruction: opcode.instruction(values),
+                });
+            }
+            _ => self.start_keyword(byte),
+        }
+
+        None
+    }
+}
+
+/// Streams tokens out of any reader through a fixed size buffer, so
+/// instructions split across reads are still recognised.
+pub struct Tokens<R: Read> {
+    reader: R,
+    tokenizer: Tokenizer,
+    buffer: [u8; BUFFER_SIZE],
+    position: usize,
+    filled: usize,
+}
+
+impl<R: Read> Tokens<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            tokenizer: Tokenizer::new(),
+            buffer: [0; BUFFER_SIZE],
+            position: 0,
+            filled: 0,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Tokens<R> {
+    type Item = io::Result<Token>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            while self.position < self.filled {
+                let byte = self.buffer[self.position];
+                self.position += 1;
+                if let Some(token) = self.tokenizer.feed(byte) {
+                    return Some(Ok(token));
+                }
+            }
+
+            self.position = 0;
+            self.filled = match self.reader.read(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(filled) => filled,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => 0,
+                Err(error) => return Some(Err(error)),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn tokenize(input: &[u8]) -> Vec<Token> {
+        Tokens::new(input).collect::<io::Result<_>>().unwrap()
+    }
+
+    /// Hands out at most `chunk` bytes per read.
+    struct ChunkedReader<'a> {
+        input: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for ChunkedReader<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            let size = self.chunk.min(buffer.len()).min(self.input.len());
+            buffer[..size].copy_from_slice(&self.input[..size]);
+            self.input = &self.input[size..];
+            Ok(size)
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        // Arrange
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        // Act
+        let tokens = tokenize(memory.as_bytes());
+
+        // Assert
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    offset: 1,
+                    length: 8,
+                    instruction: Instruction::Mul(2, 4)
+                },
+                Token {
+                    offset: 20,
+                    length: 7,
+                    instruction: Instruction::Dont
+                },
+                Token {
+                    offset: 28,
+                    length: 8,
+                    instruction: Instruction::Mul(5, 5)
+                },
+                Token {
+                    offset: 48,
+                    length: 9,
+                    instruction: Instruction::Mul(11, 8)
+                },
+                Token {
+                    offset: 59,
+                    length: 4,
+                    instruction: Instruction::Do
+                },
+                Token {
+                    offset: 64,
+                    length: 8,
+                    instruction: Instruction::Mul(8, 5)
+                },
+            ]
+        );
+    }
+
+    #[test_case("mul(1,2)", vec![Instruction::Mul(1, 2)])]
+    #[test_case("mumul(3,4)", vec![Instruction::Mul(3, 4)])]
+    #[test_case("mul(1,mul(5,6)", vec![Instruction::Mul(5, 6)])]
+    #[test_case("mul(,2)mul(1,)mul(1 ,2)mul(1,2,3)", vec![])]
+    #[test_case("dodon't()do()", vec![Instruction::Dont, Instruction::Do])]
+    #[test_case("do(1)don't(x)", vec![])]
+    #[test_case("mul(0000000000007,2)", vec![Instruction::Mul(7, 2)])]
+    #[test_case("mul(2147483648,1)mul(2,2)", vec![Instruction::Mul(2, 2)])]
+    fn test_tokenize_instructions(memory: &str, expected: Vec<Instruction>) {
+        // Arrange
+        // Act
+        let instructions: Vec<Instruction> = tokenize(memory.as_bytes())
+            .into_iter()
+            .map(|token| token.instruction)
+            .collect();
+
+        // Assert
+        assert_eq!(instructions, expected);
+    }
+
+    #[test]
+    fn test_tokenize_non_utf8() {
+        // Arrange
+        let memory = b"\xffmul(\xc3\x282,3)mul(4,5)\xfe";
+
+        // Act
+        let tokens = tokenize(memory);
+
+        // Assert
+        assert_eq!(
+            tokens,
+            vec![Token {
+                offset: 11,
+                length: 8,
+                instruction: Instruction::Mul(4, 5)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tokens_split_across_reads() {
+        // Arrange
+        let memory = b"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let expected = tokenize(memory);
+
+        for chunk in 1..=memory.len() {
+            let reader = ChunkedReader {
+                input: memory,
+                chunk,
+            };
+
+            // Act
+            let tokens: Vec<Token> = Tokens::new(reader).collect::<io::Result<_>>().unwrap();
+
+            // Assert
+            assert_eq!(tokens, expected);
+        }
+    }
+
+    #[test]
+    fn test_tokens_larger_than_buffer() {
+        // Arrange
+        let mut memory = vec![b'x'; BUFFER_SIZE - 3];
+        memory.extend_from_slice(b"mul(12,34)");
+        // Act
+        let tokens = tokenize(&memory);
+        // Assert
+        assert_eq!(
+            tokens,
+            vec![Token {
+                offset: BUFFER_SIZE - 3,
+                length: 10,
+                instruction: Instruction::Mul(12, 34)
+            }]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use crate::day03::instruction::Instruction;
+
+/// Executes instructions in order. With conditionals off, `do()` and
+/// `don't()` are ignored and every multiplication counts. A product of two
+/// `i32` arguments fits in an `i64`, and the total is kept as an `i128` so
+/// summing them cannot overflow.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Machine {
+    conditionals: bool,
+    enabled: bool,
+    total: i128,
+}
+
+impl Machine {
+    pub fn new(conditionals: bool) -> Self {
+        Self {
+            conditionals,
+            enabled: true,
+            total: 0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn total(&self) -> i128 {
+        self.total
+    }
+
+    /// Runs one instruction and returns what it added to the total.
+    pub fn execute(&mut self, instruction: &Instruction) -> i64 {
+        let contribution = match instruction {
+            Instruction::Mul(left, right) if self.enabled => i64::from(*left) * i64::from(*right),
+            Instruction::Mul(_, _) => 0,
+            Instruction::Do => {
+                self.enabled = true;
+                0
+            }
+            Instruction::Dont => {
+                self.enabled = !self.conditionals;
+                0
+            }
+        };
+
+        self.total += i128::from(contribution);
+        contribution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_machine_conditionals() {
+        // Arrange
+        let mut machine = Machine::new(true);
+        // Act
+        machine.execute(&Instruction::Mul(2, 4));
+        machine.execute(&Instruction::Dont);
+        let ignored = machine.execute(&Instruction::Mul(5, 5));
+        machine.execute(&Instruction::Do);
+        let counted = machine.execute(&Instruction::Mul(8, 5));
+        // Assert
+        assert_eq!(ignored, 0);
+        assert_eq!(counted, 40);
+        assert_eq!(machine.total(), 48);
+    }
+
+    #[test]
+    fn test_machine_without_conditionals() {
+        // Arrange
+        let mut machine = Machine::new(false);
+        // Act
+        machine.execute(&Instruction::Dont);
+        machine.execute(&Instruction::Mul(5, 5));
+        // Assert
+        assert_eq!(machine.total(), 25);
+        assert!(machine.enabled());
+    }
+
+    #[test]
+    fn test_machine_wide_product() {
+        // Arrange
+        let mut machine = Machine::new(false);
+        // Act
+        machine.execute(&Instruction::Mul(i32::MAX, i32::MAX));
+        machine.execute(&Instruction::Mul(i32::MAX, 2));
+        // Assert
+        assert_eq!(machine.total(), 4_611_686_014_132_420_609 + 4_294_967_294);
+    }
+
+    #[test]
+    fn test_machine_total_beyond_i64() {
+        // Arrange
+        let mut machine = Machine::new(false);
+        // Act
+        for _ in 0..3 {
+            machine.execute(&Instruction::Mul(i32::MAX, i32::MAX));
+        }
+        // Assert
+        assert_eq!(machine.total(), 3 * 4_611_686_014_132_420_609);
+        assert!(machine.total() > i128::from(i64::MAX));
+    }
+}
@@ -1,50 +1,76 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::fs;
+mod instruction;
+mod machine;
+mod trace;
+mod validator;
 
-static MUL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"mul\((\d+),(\d+)\)").unwrap());
-static EXTENDED_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"mul\(\d+,\d+\)|do\(\)|don't\(\)").unwrap());
+use crate::day03::instruction::Tokens;
+use crate::day03::machine::Machine;
+use crate::day03::trace::{annotate, trace};
+use crate::day03::validator::validate;
+use std::fs::{self, File};
+use std::io::{self, Read};
 
-pub(crate) fn run() {
+pub(crate) fn run(args: &[String]) {
     let filename = "data/day03/input.txt";
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
 
-    let result_part_1 = parse_memory(&contents);
+    if args.iter().any(|arg| arg == "--trace") {
+        print_trace(filename);
+    }
+
+    if args.iter().any(|arg| arg == "--strict") {
+        print_validation(filename);
+    }
+
+    let open = || File::open(filename).expect("Something went wrong reading the file");
+
+    let result_part_1 = parse_memory(open()).expect("Error reading memory");
     println!("Result of Day 03, Part 1 is {}", result_part_1);
-    let result_part_2 = parse_memory_extended(&contents);
+    let result_part_2 = parse_memory_extended(open()).expect("Error reading memory");
     println!("Result of Day 03, Part 2 is {}", result_part_2);
 }
 
-fn parse_memory(input: &str) -> i32 {
-    MUL_RE
-        .captures_iter(input)
-        .filter_map(|caps| {
-            let left = caps.get(1)?.as_str().parse::<i32>().ok();
-            let right = caps.get(2)?.as_str().parse::<i32>().ok();
-            Some(left? * right?)
-        })
-        .sum()
+fn print_trace(filename: &str) {
+    let contents = fs::read(filename).expect("Something went wrong reading the file");
+    let entries = trace(contents.as_slice(), Machine::new(true)).expect("Error reading memory");
+
+    println!(
+        "{:>8}  {:<16} {:<8} {:>8}",
+        "offset", "instruction", "state", "adds"
+    );
+    for entry in &entries {
+        println!("{}", entry);
+    }
+    println!();
+    println!("{}", annotate(&contents, &entries));
+}
+
+fn print_validation(filename: &str) {
+    let contents = fs::read(filename).expect("Something went wrong reading the file");
+
+    let part_1 = validate(&contents, Machine::new(false));
+    let part_2 = validate(&contents, Machine::new(true));
+
+    for warning in &part_1.warnings {
+        println!("Warning: {}", warning);
+    }
+    println!("Strict result of Day 03, Part 1 is {}", part_1.total);
+    println!("Strict result of Day 03, Part 2 is {}", part_2.total);
+}
+
+fn parse_memory<R: Read>(reader: R) -> io::Result<i128> {
+    execute(reader, Machine::new(false))
+}
+
+fn parse_memory_extended<R: Read>(reader: R) -> io::Result<i128> {
+    execute(reader, Machine::new(true))
 }
 
-fn parse_memory_extended(input: &str) -> i32 {
-    let mut enabled = true;
-
-    EXTENDED_RE
-        .find_iter(input)
-        .filter_map(|mat| match mat.as_str() {
-            "do()" => {
-                enabled = true;
-                None
-            }
-            "don't()" => {
-                enabled = false;
-                None
-            }
-            mul if enabled => Some(parse_memory(mul)),
-            _ => None,
-        })
-        .sum()
+fn execute<R: Read>(reader: R, mut machine: Machine) -> io::Result<i128> {
+    for token in Tokens::new(reader) {
+        machine.execute(&token?.instruction);
+    }
+
+    Ok(machine.total())
 }
 
 #[cfg(test)]
@@ -57,7 +83,7 @@ mod tests {
         let memory = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
 
         // Act
-        let result = parse_memory(memory);
+        let result = parse_memory(memory.as_bytes()).unwrap();
 
         assert_eq!(result, 161);
     }
@@ -68,8 +94,34 @@ mod tests {
         let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
 
         // Act
-        let result = parse_memory_extended(memory);
+        let result = parse_memory_extended(memory.as_bytes()).unwrap();
 
         assert_eq!(result, 48);
     }
+
+    #[test]
+    fn test_parse_memory_maximal_products() {
+        // Arrange
+        let memory = "mul(2147483647,2147483647)".repeat(3);
+
+        // Act
+        let result = parse_memory(memory.as_bytes()).unwrap();
+
+        // Assert
+        assert_eq!(result, 3 * i128::from(i32::MAX).pow(2));
+    }
+
+    #[test]
+    fn test_parse_memory_non_utf8() {
+        // Arrange
+        let memory: &[u8] = b"\xffmul(2,4)\xc3don't()\x80mul(5,5)do()\xfemul(8,5)";
+
+        // Act
+        let result = parse_memory(memory).unwrap();
+        let result_extended = parse_memory_extended(memory).unwrap();
+
+        // Assert
+        assert_eq!(result, 73);
+        assert_eq!(result_extended, 48);
+    }
 }
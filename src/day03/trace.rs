@@ -0,0 +1,128 @@
+use crate::day03::instruction::{Instruction, Token, Tokens};
+use crate::day03::machine::Machine;
+use std::fmt;
+use std::io::{self, Read};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// One executed instruction, with whether the machine was enabled when it
+/// ran and what it added to the total.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TraceEntry {
+    pub token: Token,
+    pub enabled: bool,
+    pub contribution: i64,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8}  {:<16} {:<8} {:>8}",
+            self.token.offset,
+            self.token.instruction.to_string(),
+            if self.enabled { "enabled" } else { "disabled" },
+            self.contribution
+        )
+    }
+}
+
+pub fn trace<R: Read>(reader: R, mut machine: Machine) -> io::Result<Vec<TraceEntry>> {
+    Tokens::new(reader)
+        .map(|token| {
+            let token = token?;
+            let enabled = machine.enabled();
+            let contribution = machine.execute(&token.instruction);
+
+            Ok(TraceEntry {
+                token,
+                enabled,
+                contribution,
+            })
+        })
+        .collect()
+}
+
+/// Renders the input with counted multiplications in green, ignored ones in
+/// red and conditionals in yellow. Invalid UTF-8 is shown as U+FFFD.
+pub fn annotate(input: &[u8], entries: &[TraceEntry]) -> String {
+    let mut annotated = String::new();
+    let mut position = 0;
+
+    for entry in entries {
+        let Token { offset, length, .. } = entry.token;
+        let colour = match entry.token.instruction {
+            Instruction::Mul(_, _) if entry.enabled => GREEN,
+            Instruction::Mul(_, _) => RED,
+            Instruction::Do | Instruction::Dont => YELLOW,
+        };
+
+        annotated.push_str(&String::from_utf8_lossy(&input[position..offset]));
+        annotated.push_str(colour);
+        annotated.push_str(&String::from_utf8_lossy(&input[offset..offset + length]));
+        annotated.push_str(RESET);
+        position = offset + length;
+    }
+
+    annotated.push_str(&String::from_utf8_lossy(&input[position..]));
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMORY: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn test_trace() {
+        // Arrange
+        let machine = Machine::new(true);
+        // Act
+        let entries = trace(MEMORY.as_bytes(), machine).unwrap();
+        // Assert
+        let summary: Vec<(usize, bool, i64)> = entries
+            .iter()
+            .map(|entry| (entry.token.offset, entry.enabled, entry.contribution))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, true, 8),
+                (20, true, 0),
+                (28, false, 0),
+                (48, false, 0),
+                (59, false, 0),
+                (64, true, 40)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_entry_display() {
+        // Arrange
+        let entries = trace(MEMORY.as_bytes(), Machine::new(true)).unwrap();
+        // Act
+        let line = entries[0].to_string();
+        // Assert
+        assert_eq!(line, "       1  mul(2,4)         enabled         8");
+    }
+
+    #[test]
+    fn test_annotate() {
+        // Arrange
+        let memory = "?mul(2,4)don't()mul(1,1)!";
+        let entries = trace(memory.as_bytes(), Machine::new(true)).unwrap();
+        // Act
+        let annotated = annotate(memory.as_bytes(), &entries);
+        // Assert
+        assert_eq!(
+            annotated,
+            "?\x1b[32mmul(2,4)\x1b[0m\x1b[33mdon't()\x1b[0m\x1b[31mmul(1,1)\x1b[0m!"
+        );
+    }
+}
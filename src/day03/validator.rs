@@ -0,0 +1,231 @@
+use crate::day03::instruction::Instruction;
+use crate::day03::machine::Machine;
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, digit1, one_of, space0},
+    combinator::{map, map_res, recognize, value, verify},
+    sequence::{delimited, separated_pair, tuple},
+    IResult,
+};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WarningKind {
+    TooManyDigits,
+    Whitespace,
+    WrongDelimiter,
+}
+
+/// Text that looks like a `mul` instruction but is not valid under the
+/// strict grammar, so it was skipped.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Warning {
+    pub offset: usize,
+    pub text: String,
+    pub kind: WarningKind,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Validation {
+    pub total: i128,
+    pub warnings: Vec<Warning>,
+}
+
+/// Runs the memory through `machine` accepting only `mul(X,Y)` with one to
+/// three digit arguments, `do()` and `don't()`. Works on raw bytes, so
+/// warning offsets match the ones in the trace even for invalid UTF-8.
+pub fn validate(input: &[u8], mut machine: Machine) -> Validation {
+    let mut warnings = vec![];
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let offset = input.len() - rest.len();
+
+        if let Ok((next, instruction)) = instruction(rest) {
+            machine.execute(&instruction);
+            rest = next;
+        } else if let Ok((next, text)) = near_miss(rest) {
+            let text = String::from_utf8_lossy(text).into_owned();
+            warnings.push(Warning {
+                offset,
+                kind: warning_kind(&text),
+                text,
+            });
+            rest = next;
+        } else {
+            rest = &rest[1..];
+        }
+    }
+
+    Validation {
+        total: machine.total(),
+        warnings,
+    }
+}
+
+fn number(input: &[u8]) -> IResult<&[u8], i32> {
+    map_res(
+        map_res(
+            verify(digit1, |digits: &[u8]| digits.len() <= 3),
+            str::from_utf8,
+        ),
+        str::parse,
+    )(input)
+}
+
+fn mul(input: &[u8]) -> IResult<&[u8], Instruction> {
+    map(
+        delimited(
+            tag("mul("),
+            separated_pair(number, char(','), number),
+            char(')'),
+        ),
+        |(left, right)| Instruction::Mul(left, right),
+    )(input)
+}
+
+fn instruction(input: &[u8]) -> IResult<&[u8], Instruction> {
+    alt((
+        mul,
+        value(Instruction::Do, tag("do()")),
+        value(Instruction::Dont, tag("don't()")),
+    ))(input)
+}
+
+fn near_miss(input: &[u8]) -> IResult<&[u8], &[u8]> {
+    recognize(tuple((
+        tag("mul"),
+        space0,
+        one_of("([{<"),
+        space0,
+        digit1,
+        space0,
+        one_of(",;"),
+        space0,
+        digit1,
+        space0,
+        one_of(")]}>"),
+    )))(input)
+}
+
+fn warning_kind(text: &str) -> WarningKind {
+    let too_many_digits = text
+        .split(|c: char| !c.is_ascii_digit())
+        .any(|digits| digits.len() > 3);
+
+    if too_many_digits {
+        WarningKind::TooManyDigits
+    } else if text.contains(char::is_whitespace) {
+        WarningKind::Whitespace
+    } else {
+        WarningKind::WrongDelimiter
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.kind {
+            WarningKind::TooManyDigits => "argument with more than 3 digits",
+            WarningKind::Whitespace => "whitespace inside instruction",
+            WarningKind::WrongDelimiter => "wrong delimiter",
+        };
+
+        write!(f, "offset {}: {} ({})", self.offset, self.text, reason)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_validate() {
+        // Arrange
+        let memory = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+
+        // Act
+        let validation = validate(memory.as_bytes(), Machine::new(false));
+
+        // Assert
+        assert_eq!(validation.total, 161);
+        assert_eq!(
+            validation.warnings,
+            vec![
+                Warning {
+                    offset: 11,
+                    text: "mul[3,7]".to_string(),
+                    kind: WarningKind::WrongDelimiter
+                },
+                Warning {
+                    offset: 38,
+                    text: "mul(32,64]".to_string(),
+                    kind: WarningKind::WrongDelimiter
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_extended() {
+        // Arrange
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        // Act
+        let validation = validate(memory.as_bytes(), Machine::new(true));
+
+        // Assert
+        assert_eq!(validation.total, 48);
+        assert_eq!(validation.warnings.len(), 2);
+    }
+
+    #[test_case("mul(1234,5)", WarningKind::TooManyDigits)]
+    #[test_case("mul(0001,5)", WarningKind::TooManyDigits)]
+    #[test_case("mul( 2,4)", WarningKind::Whitespace)]
+    #[test_case("mul(2;4)", WarningKind::WrongDelimiter)]
+    #[test_case("mul{2,4}", WarningKind::WrongDelimiter)]
+    fn test_validate_near_miss(memory: &str, expected: WarningKind) {
+        // Arrange
+        // Act
+        let validation = validate(memory.as_bytes(), Machine::new(false));
+
+        // Assert
+        assert_eq!(validation.total, 0);
+        assert_eq!(validation.warnings.len(), 1);
+        assert_eq!(validation.warnings[0].kind, expected);
+    }
+
+    #[test]
+    fn test_validate_non_utf8_offsets() {
+        // Arrange
+        let memory = b"\xff\xfemul(2,4)\xc3mul[3,7]";
+
+        // Act
+        let validation = validate(memory, Machine::new(false));
+
+        // Assert
+        assert_eq!(validation.total, 8);
+        assert_eq!(
+            validation.warnings,
+            vec![Warning {
+                offset: 11,
+                text: "mul[3,7]".to_string(),
+                kind: WarningKind::WrongDelimiter
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_ignores_unrelated_text() {
+        // Arrange
+        let memory = "mul(2,mul(3,4))mul(,)mulé(1,1)mul(999,999)";
+
+        // Act
+        let validation = validate(memory.as_bytes(), Machine::new(false));
+
+        // Assert
+        assert_eq!(validation.total, 12 + 999 * 999);
+        assert!(validation.warnings.is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::day04::word_search::{Direction, WordMatch};
+use aho_corasick::AhoCorasick;
+
+/// The four reading directions that are scanned as views; the opposite
+/// directions are covered by searching for the reversed words.
+const VIEWS: [(Direction, Direction); 4] = [
+    (Direction::East, Direction::West),
+    (Direction::South, Direction::North),
+    (Direction::SouthEast, Direction::NorthWest),
+    (Direction::NorthEast, Direction::SouthWest),
+];
+
+/// A set of words searched for together with a single Aho–Corasick
+/// automaton. The automaton holds every word forwards and backwards, so one
+/// pass over each row, column and diagonal finds all eight directions.
+pub struct Dictionary {
+    words: Vec<String>,
+    automaton: AhoCorasick,
+    /// For each automaton pattern, the word it belongs to and whether it is
+    /// that word reversed.
+    patterns: Vec<(usize, bool)>,
+}
+
+/// Occurrences of each dictionary word, in the order of
+/// [`Dictionary::words`].
+pub struct DictionaryMatches<'a> {
+    dictionary: &'a Dictionary,
+    positions: Vec<Vec<WordMatch>>,
+}
+
+/// A straight run of existing cells and the text read along it.
+struct View {
+    cells: Vec<(usize, usize)>,
+    text: String,
+    offsets: Vec<usize>,
+}
+
+impl Dictionary {
+    /// Duplicate words are kept once; empty words never match.
+    pub fn new(words: &[&str]) -> Self {
+        let mut unique: Vec<String> = vec![];
+        for word in words {
+            if !unique.iter().any(|existing| existing == word) {
+                unique.push(word.to_string());
+            }
+        }
+
+        let mut patterns = vec![];
+        let mut texts = vec![];
+        for (index, word) in unique.iter().enumerate() {
+            if word.is_empty() {
+                continue;
+            }
+            patterns.push((index, false));
+            texts.push(word.clone());
+            patterns.push((index, true));
+            texts.push(word.chars().rev().collect());
+        }
+
+        let automaton = AhoCorasick::new(&texts).expect("Error building automaton");
+
+        Self {
+            words: unique,
+            automaton,
+            patterns,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn search(&self, grid: &[Vec<char>]) -> DictionaryMatches<'_> {
+        let mut positions = vec![vec![]; self.words.len()];
+
+        for (forward, backward) in VIEWS {
+            for view in View::all(grid, forward) {
+                for found in self.automaton.find_overlapping_iter(&view.text) {
+                    let (word, reversed) = self.patterns[found.pattern().as_usize()];
+                    let first = view.char_index(found.start());
+                    let last = view.char_index(found.end()) - 1;
+
+                    let (row, col, direction) = if reversed {
+                        let (row, col) = view.cells[last];
+                        (row, col, backward)
+                    } else {
+                        let (row, col) = view.cells[first];
+                        (row, col, forward)
+                    };
+                    positions[word].push(WordMatch {
+                        row,
+                        col,
+                        direction,
+                    });
+                }
+            }
+        }
+
+        for matches in positions.iter_mut() {
+            matches.sort();
+        }
+
+        DictionaryMatches {
+            dictionary: self,
+            positions,
+        }
+    }
+}
+
+impl DictionaryMatches<'_> {
+    #[allow(dead_code)]
+    pub fn count(&self, word: &str) -> usize {
+        self.positions(word).len()
+    }
+
+    /// Matches of `word` in row-major order of the first letter, or nothing
+    /// if it is not in the dictionary.
+    #[allow(dead_code)]
+    pub fn positions(&self, word: &str) -> &[WordMatch] {
+        self.dictionary
+            .words
+            .iter()
+            .position(|existing| existing == word)
+            .map_or(&[], |index| &self.positions[index])
+    }
+
+    #[allow(dead_code)]
+    pub fn total(&self) -> usize {
+        self.positions.iter().map(Vec::len).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[WordMatch])> {
+        self.dictionary
+            .words
+            .iter()
+            .map(String::as_str)
+            .zip(self.positions.iter().map(Vec::as_slice))
+    }
+}
+
+impl View {
+    /// Every maximal run of cells along `direction`. A run starts at each
+    /// cell whose predecessor is missing, so short rows split the run
+    /// instead of being read across.
+    fn all(grid: &[Vec<char>], direction: Direction) -> Vec<View> {
+        let (row_step, col_step) = direction.delta();
+        let mut views = vec![];
+
+        for (row, line) in grid.iter().enumerate() {
+            for col in 0..line.len() {
+                if Self::get(grid, row as isize - row_step, col as isize - col_step).is_some() {
+                    continue;
+                }
+
+                let mut view = View {
+                    cells: vec![],
+                    text: String::new(),
+                    offsets: vec![],
+                };
+                let (mut r, mut c) = (row as isize, col as isize);
+                while let Some(letter) = Self::get(grid, r, c) {
+                    view.cells.push((r as usize, c as usize));
+                    view.offsets.push(view.text.len());
+                    view.text.push(letter);
+                    r += row_step;
+                    c += col_step;
+                }
+                views.push(view);
+            }
+        }
+
+        views
+    }
+
+    fn get(grid: &[Vec<char>], row: isize, col: isize) -> Option<char> {
+        let row = usize::try_from(row).ok()?;
+        let col = usize::try_from(col).ok()?;
+        grid.get(row).and_then(|line| line.get(col)).copied()
+    }
+
+    /// Index of the letter starting at byte `offset`, where the end of the
+    /// text counts as one past the last letter.
+    fn char_index(&self, offset: usize) -> usize {
+        self.offsets
+            .binary_search(&offset)
+            .unwrap_or(self.offsets.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day04::word_search::WordSearch;
+    use crate::day04::{parse_matrix, read_matrix_from_file};
+    use test_case::test_case;
+
+    #[test]
+    fn test_search_xmas() {
+        // Arrange
+        let matrix = read_matrix_from_file("data/day04/example.txt");
+        let dictionary = Dictionary::new(&["XMAS"]);
+        // Act
+        let matches = dictionary.search(&matrix);
+        // Assert
+        assert_eq!(matches.count("XMAS"), 18);
+    }
+
+    #[test]
+    fn test_search_counts_per_word() {
+        // Arrange
+        let matrix = read_matrix_from_file("data/day04/example.txt");
+        let dictionary = Dictionary::new(&["XMAS", "MAS", "SAM", "AXA", "QQQ"]);
+        // Act
+        let matches = dictionary.search(&matrix);
+        // Assert
+        for word in dictionary.words() {
+            let expected = WordSearch::new(&matrix).find(word, &Direction::ALL);
+            assert_eq!(matches.positions(word), expected, "{}", word);
+        }
+        assert_eq!(matches.count("QQQ"), 0);
+    }
+
+    #[test]
+    fn test_search_positions() {
+        // Arrange
+        let grid = parse_matrix("CAT\nAXA\nTAC");
+        let dictionary = Dictionary::new(&["CAT"]);
+        // Act
+        let matches = dictionary.search(&grid);
+        // Assert
+        assert_eq!(
+            matches.positions("CAT"),
+            WordSearch::new(&grid).find("CAT", &Direction::ALL)
+        );
+    }
+
+    #[test_case("AB\nABC\nA", &["AA", "BC", "CB"])]
+    #[test_case("ABA\nB\nABA", &["ABA", "A", "BB"])]
+    #[test_case("ÄÖ\nÖÄ", &["ÄÄ", "ÖÖ", "ÄÖ"]; "non ascii letters")]
+    #[test_case("", &["X"])]
+    fn test_search_agrees_with_word_search(input: &str, words: &[&str]) {
+        // Arrange
+        let grid = parse_matrix(input);
+        let dictionary = Dictionary::new(words);
+        // Act
+        let matches = dictionary.search(&grid);
+        // Assert
+        for (word, positions) in matches.iter() {
+            let expected = WordSearch::new(&grid).find(word, &Direction::ALL);
+            assert_eq!(positions, expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_and_empty_words() {
+        // Arrange
+        let grid = parse_matrix("XMAS");
+        let dictionary = Dictionary::new(&["XMAS", "", "XMAS"]);
+        // Act
+        let matches = dictionary.search(&grid);
+        // Assert
+        assert_eq!(dictionary.words(), ["XMAS", ""]);
+        assert_eq!(matches.count("XMAS"), 1);
+        assert_eq!(matches.count(""), 0);
+        assert_eq!(matches.total(), 1);
+    }
+}
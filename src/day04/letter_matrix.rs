@@ -0,0 +1,378 @@
+use crate::day04::dictionary::{Dictionary, DictionaryMatches};
+use crate::day04::pattern::{Pattern, Symmetry};
+use crate::day04::render::{render, Style};
+use crate::day04::word_search::{Direction, WordSearch};
+use crate::day04::{parse_matrix, read_matrix_from_file};
+use std::collections::HashSet;
+
+/// Stands in for cells missing from short rows, so words are never read
+/// across a gap.
+const GAP: char = ' ';
+
+/// Letter grid for both puzzle parts. Rows may differ in length; `cols` is
+/// the length of the longest one.
+pub struct LetterMatrix {
+    matrix: Vec<Vec<char>>,
+    rows: usize,
+    cols: usize,
+}
+
+impl LetterMatrix {
+    pub fn from_file(filename: &str) -> Self {
+        Self::new(read_matrix_from_file(filename))
+    }
+
+    #[allow(dead_code)]
+    pub fn from_string(input: &str) -> Self {
+        Self::new(parse_matrix(input))
+    }
+
+    fn new(matrix: Vec<Vec<char>>) -> Self {
+        let rows = matrix.len();
+        let cols = matrix.iter().map(Vec::len).max().unwrap_or(0);
+
+        Self { matrix, rows, cols }
+    }
+
+    pub fn word_search(&self) -> WordSearch<'_> {
+        WordSearch::new(&self.matrix)
+    }
+
+    pub fn dictionary_search<'d>(&self, dictionary: &'d Dictionary) -> DictionaryMatches<'d> {
+        dictionary.search(&self.matrix)
+    }
+
+    pub fn count_xmas(&self) -> i32 {
+        let diagonals = (self.rows + self.cols).saturating_sub(1);
+
+        (0..self.rows)
+            .map(|i| self.line(i))
+            .chain((0..self.cols).map(|i| self.column(i)))
+            .chain((0..diagonals).map(|i| self.diagonal_positive(i)))
+            .chain((0..diagonals).map(|i| self.diagonal_negative(i)))
+            .map(|sequence| Self::count_xmas_words(&sequence))
+            .sum()
+    }
+
+    pub fn count_xmas_second(&self) -> i32 {
+        Pattern::x_mas()
+            .find(&self.matrix, Symmetry::Rotations)
+            .len() as i32
+    }
+
+    /// Cells that are part of at least one XMAS.
+    pub fn xmas_cells(&self) -> HashSet<(usize, usize)> {
+        let word = "XMAS";
+
+        self.word_search()
+            .find(word, &Direction::ALL)
+            .iter()
+            .flat_map(|found| found.cells(word.len()))
+            .collect()
+    }
+
+    /// Cells that are part of at least one X-MAS.
+    pub fn x_mas_cells(&self) -> HashSet<(usize, usize)> {
+        let variants = Pattern::x_mas().variants(Symmetry::Rotations);
+
+        Pattern::x_mas()
+            .find(&self.matrix, Symmetry::Rotations)
+            .iter()
+            .flat_map(|found| variants[found.variant].cells_at(found.row, found.col))
+            .collect()
+    }
+
+    pub fn render(&self, highlighted: &HashSet<(usize, usize)>, style: Style) -> String {
+        render(&self.matrix, highlighted, style)
+    }
+
+    fn get(&self, row: usize, col: usize) -> char {
+        self.matrix
+            .get(row)
+            .and_then(|line| line.get(col))
+            .copied()
+            .unwrap_or(GAP)
+    }
+
+    fn line(&self, index: usize) -> String {
+        self.matrix[index].iter().collect()
+    }
+
+    fn column(&self, index: usize) -> String {
+        (0..self.rows).map(|row| self.get(row, index)).collect()
+    }
+
+    /// Cells with `row + col == index`, read from bottom-left to top-right.
+    fn diagonal_positive(&self, index: usize) -> String {
+        let mut row = index.min(self.rows - 1);
+        let mut col = index - row;
+        let mut diagonal = String::new();
+
+        while col < self.cols {
+            diagonal.push(self.get(row, col));
+
+            if row == 0 {
+                break;
+            }
+
+            row -= 1;
+            col += 1;
+        }
+
+        diagonal
+    }
+
+    /// Cells with `col - row == index - (rows - 1)`, read from top-left to
+    /// bottom-right.
+    fn diagonal_negative(&self, index: usize) -> String {
+        let (mut row, mut col) = if index < self.rows {
+            (self.rows - index - 1, 0)
+        } else {
+            (0, index - self.rows + 1)
+        };
+
+        let mut diagonal = String::new();
+
+        while row < self.rows && col < self.cols {
+            diagonal.push(self.get(row, col));
+
+            row += 1;
+            col += 1;
+        }
+
+        diagonal
+    }
+
+    fn count_xmas_words(line: &str) -> i32 {
+        let patterns = ["XMAS", "SAMX"];
+        patterns
+            .iter()
+            .map(|&pattern| line.match_indices(pattern).count() as i32)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_count_xmas() {
+        // Arrange
+        let line: Vec<&str> = "XMASAMXAMM".split("").collect();
+        let joined = line.join("");
+        // Act
+        let count = LetterMatrix::count_xmas_words(&joined);
+        // Assert
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_create_matrix() {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        // Act
+        let matrix = LetterMatrix::from_file(filename);
+        // Assert
+        assert_eq!(matrix.matrix[0][0], 'M');
+        assert_eq!(matrix.matrix[2][0], 'A');
+        assert_eq!(matrix.matrix[0][2], 'M');
+    }
+
+    #[test]
+    fn test_get_line() {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let line = matrix.line(0);
+        // Assert
+        assert_eq!(line, "MMMSXXMASM");
+    }
+
+    #[test]
+    fn test_get_column() {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let line = matrix.column(0);
+        // Assert
+        assert_eq!(line, "MMAMXXSSMM");
+    }
+
+    #[test_case(0, "M")]
+    #[test_case(1, "MM")]
+    #[test_case(17, "SM")]
+    #[test_case(18, "X")]
+    fn test_diagonal_positive(index: usize, expected: &str) {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let diagonal = matrix.diagonal_positive(index);
+        // Assert
+        assert_eq!(diagonal, expected);
+    }
+
+    #[test_case(0, "M")]
+    #[test_case(1, "MX")]
+    #[test_case(17, "SA")]
+    #[test_case(18, "M")]
+    fn test_diagonal_negative(index: usize, expected: &str) {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let diagonal = matrix.diagonal_negative(index);
+        // Assert
+        assert_eq!(diagonal, expected);
+    }
+
+    #[test_case("ABCD\nEFGH", vec!["A", "EB", "FC", "GD", "H"])]
+    #[test_case("AB\nCD\nEF\nGH", vec!["A", "CB", "ED", "GF", "H"])]
+    fn test_diagonal_positive_rectangular(input: &str, expected: Vec<&str>) {
+        // Arrange
+        let matrix = LetterMatrix::from_string(input);
+        // Act
+        let diagonals: Vec<String> = (0..expected.len())
+            .map(|index| matrix.diagonal_positive(index))
+            .collect();
+        // Assert
+        assert_eq!(diagonals, expected);
+    }
+
+    #[test_case("ABCD\nEFGH", vec!["E", "AF", "BG", "CH", "D"])]
+    #[test_case("AB\nCD\nEF\nGH", vec!["G", "EH", "CF", "AD", "B"])]
+    fn test_diagonal_negative_rectangular(input: &str, expected: Vec<&str>) {
+        // Arrange
+        let matrix = LetterMatrix::from_string(input);
+        // Act
+        let diagonals: Vec<String> = (0..expected.len())
+            .map(|index| matrix.diagonal_negative(index))
+            .collect();
+        // Assert
+        assert_eq!(diagonals, expected);
+    }
+
+    #[test]
+    fn test_matrix_count_xmas() {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let count = matrix.count_xmas();
+        // Assert
+        assert_eq!(count, 18);
+    }
+
+    #[test_case("XMASAMX\nMMMMMMM\nAAAAAAA\nSSSSSSS", 6)]
+    #[test_case("X\nM\nA\nS\nA\nM\nX", 2)]
+    #[test_case("XMAS\nM\nA\nSAMX", 3)]
+    #[test_case("XMAS\n\nXMAS", 2)]
+    #[test_case("X\nMM\nAAA\nSSSS", 2)]
+    #[test_case("", 0)]
+    fn test_matrix_count_xmas_non_square(input: &str, expected: i32) {
+        // Arrange
+        let matrix = LetterMatrix::from_string(input);
+        // Act
+        let count = matrix.count_xmas();
+        let searched = matrix.word_search().find("XMAS", &Direction::ALL).len();
+        // Assert
+        assert_eq!(count, expected);
+        assert_eq!(searched as i32, expected);
+    }
+
+    #[test]
+    fn test_dictionary_search_matches_count_xmas() {
+        // Arrange
+        let matrix = LetterMatrix::from_file("data/day04/example.txt");
+        let dictionary = Dictionary::new(&["XMAS", "MAS"]);
+        // Act
+        let matches = matrix.dictionary_search(&dictionary);
+        // Assert
+        assert_eq!(matches.count("XMAS") as i32, matrix.count_xmas());
+    }
+
+    #[test]
+    fn test_render_xmas() {
+        // Arrange
+        let matrix = LetterMatrix::from_file("data/day04/example.txt");
+        // Act
+        let rendered = matrix.render(&matrix.xmas_cells(), Style::Dots);
+        // Assert
+        assert_eq!(
+            rendered,
+            "....XXMAS.\n\
+             .SAMXMS...\n\
+             ...S..A...\n\
+             ..A.A.MS.X\n\
+             XMASAMX.MM\n\
+             X.....XA.A\n\
+             S.S.S.S.SS\n\
+             .A.A.A.A.A\n\
+             ..M.M.M.MM\n\
+             .X.X.XMASX\n"
+        );
+    }
+
+    #[test]
+    fn test_render_x_mas() {
+        // Arrange
+        let matrix = LetterMatrix::from_file("data/day04/example.txt");
+        // Act
+        let rendered = matrix.render(&matrix.x_mas_cells(), Style::Dots);
+        // Assert
+        assert_eq!(
+            rendered,
+            ".M.S......\n\
+             ..A..MSMS.\n\
+             .M.S.MAA..\n\
+             ..A.ASMSM.\n\
+             .M.S.M....\n\
+             ..........\n\
+             S.S.S.S.S.\n\
+             .A.A.A.A..\n\
+             M.M.M.M.M.\n\
+             ..........\n"
+        );
+    }
+
+    #[test]
+    fn test_matrix_found_mas() {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let found = Pattern::x_mas()
+            .variants(Symmetry::Rotations)
+            .iter()
+            .any(|pattern| pattern.matches_at(&matrix.matrix, 0, 1));
+        // Assert
+        assert!(found);
+    }
+
+    #[test]
+    fn test_matrix_count_xmas_second() {
+        // Arrange
+        let filename = "data/day04/example.txt";
+        let matrix = LetterMatrix::from_file(filename);
+        // Act
+        let count = matrix.count_xmas_second();
+        // Assert
+        assert_eq!(count, 9);
+    }
+
+    #[test_case("M.S..\n.A...\nM.S..", 1)]
+    #[test_case("S.S.M\n.A.A.\nM.M.M\n...\n", 1)]
+    #[test_case("M.S\n.A.\nM.S\nXX", 1)]
+    fn test_matrix_count_xmas_second_non_square(input: &str, expected: i32) {
+        // Arrange
+        let matrix = LetterMatrix::from_string(input);
+        // Act
+        let count = matrix.count_xmas_second();
+        // Assert
+        assert_eq!(count, expected);
+    }
+}
@@ -1,27 +1,66 @@
-mod matrix_one;
-mod matrix_two;
+mod dictionary;
+mod letter_matrix;
+mod pattern;
+mod render;
+mod word_search;
 
-use crate::day04::matrix_one::MatrixOne;
-use crate::day04::matrix_two::MatrixTwo;
+use crate::day04::dictionary::Dictionary;
+use crate::day04::letter_matrix::LetterMatrix;
+use crate::day04::render::{Style, Target};
+use crate::day04::word_search::Direction;
+use crate::utils::option_value;
 use std::fs;
 
-pub(crate) fn run() {
+pub(crate) fn run(args: &[String]) {
     let filename = "data/day04/input.txt";
-    let matrix = MatrixOne::from_file(filename);
+    let matrix = LetterMatrix::from_file(filename);
+    let target = match option_value(args, "--render")
+        .map(Target::from_arg)
+        .transpose()
+    {
+        Ok(target) => target,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return;
+        }
+    };
+
+    if let Some(word) = option_value(args, "--find") {
+        for found in matrix.word_search().find(word, &Direction::ALL) {
+            println!("{} at {}", word, found);
+        }
+    }
+
+    if let Some(path) = option_value(args, "--dictionary") {
+        let contents = fs::read_to_string(path).expect("Error reading dictionary");
+        let words: Vec<&str> = contents.split_whitespace().collect();
+        let dictionary = Dictionary::new(&words);
+        for (word, positions) in matrix.dictionary_search(&dictionary).iter() {
+            println!("{}: {}", word, positions.len());
+        }
+    }
+
+    if let Some(target) = target {
+        let highlighted = match target {
+            Target::Xmas => matrix.xmas_cells(),
+            Target::XMas => matrix.x_mas_cells(),
+        };
+        let style = if args.iter().any(|arg| arg == "--color") {
+            Style::Color
+        } else {
+            Style::Dots
+        };
+        print!("{}", matrix.render(&highlighted, style));
+    }
+
     println!("Result of Day 04, Part 1 is {}", matrix.count_xmas());
-    let matrix = MatrixTwo::from_file(filename);
     println!("Result of Day 04, Part 2 is {}", matrix.count_xmas_second());
 }
 
-fn read_matrix_from_file(filename: &str) -> (Vec<Vec<char>>, usize, usize) {
-    let matrix = fs::read_to_string(filename)
-        .expect("Error reading file")
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect::<Vec<Vec<char>>>();
-
-    let rows = matrix.len();
-    let cols = matrix.first().unwrap_or(&vec![]).len();
+fn read_matrix_from_file(filename: &str) -> Vec<Vec<char>> {
+    parse_matrix(&fs::read_to_string(filename).expect("Error reading file"))
+}
 
-    (matrix, rows, cols)
+fn parse_matrix(input: &str) -> Vec<Vec<char>> {
+    input.lines().map(|line| line.chars().collect()).collect()
 }
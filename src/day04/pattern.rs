@@ -0,0 +1,259 @@
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Symmetry {
+    None,
+    Rotations,
+    RotationsAndReflections,
+}
+
+/// A rectangular template where `None` cells match any letter.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Pattern {
+    cells: Vec<Vec<Option<char>>>,
+}
+
+/// Top-left corner of a match and the index of the variant, as returned by
+/// [`Pattern::variants`], that matched there.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct PatternMatch {
+    pub row: usize,
+    pub col: usize,
+    pub variant: usize,
+}
+
+impl Pattern {
+    pub const WILDCARD: char = '.';
+
+    /// Builds a pattern from one line per row, with `.` as the wildcard.
+    /// Short rows are padded with wildcards.
+    pub fn parse(template: &str) -> Pattern {
+        let rows: Vec<Vec<Option<char>>> = template
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| (c != Self::WILDCARD).then_some(c))
+                    .collect()
+            })
+            .collect();
+        let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        let cells = rows
+            .into_iter()
+            .map(|mut row| {
+                row.resize(cols, None);
+                row
+            })
+            .collect();
+
+        Self { cells }
+    }
+
+    pub fn x_mas() -> Pattern {
+        Self::parse("M.S\n.A.\nM.S")
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.first().map_or(0, Vec::len)
+    }
+
+    /// The distinct orientations allowed by `symmetry`, starting with the
+    /// pattern itself.
+    pub fn variants(&self, symmetry: Symmetry) -> Vec<Pattern> {
+        let mut candidates = vec![self.clone()];
+
+        if symmetry != Symmetry::None {
+            for _ in 0..3 {
+                let rotated = candidates.last().unwrap().rotate();
+                candidates.push(rotated);
+            }
+        }
+        if symmetry == Symmetry::RotationsAndReflections {
+            let reflected: Vec<Pattern> = candidates.iter().map(Pattern::reflect).collect();
+            candidates.extend(reflected);
+        }
+
+        let mut variants: Vec<Pattern> = vec![];
+        for candidate in candidates {
+            if !variants.contains(&candidate) {
+                variants.push(candidate);
+            }
+        }
+        variants
+    }
+
+    pub fn find(&self, grid: &[Vec<char>], symmetry: Symmetry) -> Vec<PatternMatch> {
+        let variants = self.variants(symmetry);
+        let mut matches = vec![];
+
+        for (row, line) in grid.iter().enumerate() {
+            for col in 0..line.len() {
+                for (variant, pattern) in variants.iter().enumerate() {
+                    if pattern.matches_at(grid, row, col) {
+                        matches.push(PatternMatch { row, col, variant });
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+
+    pub fn matches_at(&self, grid: &[Vec<char>], row: usize, col: usize) -> bool {
+        self.cells.iter().enumerate().all(|(dr, cells)| {
+            cells.iter().enumerate().all(|(dc, cell)| {
+                let letter = grid.get(row + dr).and_then(|line| line.get(col + dc));
+                match cell {
+                    Some(expected) => letter == Some(expected),
+                    None => letter.is_some(),
+                }
+            })
+        })
+    }
+
+    /// Grid cells of the letters, not the wildcards, when the pattern is
+    /// placed with its top-left corner at `row`, `col`.
+    pub fn cells_at(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(dr, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.is_some())
+                    .map(move |(dc, _)| (row + dr, col + dc))
+            })
+            .collect()
+    }
+
+    /// Quarter turn clockwise.
+    fn rotate(&self) -> Pattern {
+        let cells = (0..self.cols())
+            .map(|col| {
+                (0..self.rows())
+                    .rev()
+                    .map(|row| self.cells[row][col])
+                    .collect()
+            })
+            .collect();
+
+        Self { cells }
+    }
+
+    /// Mirror across the vertical axis.
+    fn reflect(&self) -> Pattern {
+        let cells = self
+            .cells
+            .iter()
+            .map(|row| row.iter().rev().copied().collect())
+            .collect();
+
+        Self { cells }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day04::{parse_matrix, read_matrix_from_file};
+    use test_case::test_case;
+
+    #[test]
+    fn test_parse_pads_with_wildcards() {
+        // Arrange
+        // Act
+        let pattern = Pattern::parse("AB\nC");
+        // Assert
+        assert_eq!(
+            pattern.cells,
+            vec![vec![Some('A'), Some('B')], vec![Some('C'), None]]
+        );
+    }
+
+    #[test]
+    fn test_rotate() {
+        // Arrange
+        let pattern = Pattern::parse("AB\nCD\nEF");
+        // Act
+        let rotated = pattern.rotate();
+        // Assert
+        assert_eq!(rotated, Pattern::parse("ECA\nFDB"));
+    }
+
+    #[test_case("M.S\n.A.\nM.S", Symmetry::None, 1)]
+    #[test_case("M.S\n.A.\nM.S", Symmetry::Rotations, 4)]
+    #[test_case("M.S\n.A.\nM.S", Symmetry::RotationsAndReflections, 4)]
+    #[test_case(".A.\nAAA\n.A.", Symmetry::RotationsAndReflections, 1)]
+    #[test_case("AB\n.C", Symmetry::Rotations, 4)]
+    #[test_case("AB\n.C", Symmetry::RotationsAndReflections, 8)]
+    #[test_case("ABC", Symmetry::Rotations, 4)]
+    #[test_case("ABA", Symmetry::RotationsAndReflections, 2)]
+    fn test_variants(template: &str, symmetry: Symmetry, expected: usize) {
+        // Arrange
+        let pattern = Pattern::parse(template);
+        // Act
+        let variants = pattern.variants(symmetry);
+        // Assert
+        assert_eq!(variants.len(), expected);
+    }
+
+    #[test]
+    fn test_find_x_mas() {
+        // Arrange
+        let matrix = read_matrix_from_file("data/day04/example.txt");
+        // Act
+        let matches = Pattern::x_mas().find(&matrix, Symmetry::Rotations);
+        // Assert
+        assert_eq!(matches.len(), 9);
+        assert!(matches.iter().any(|found| (found.row, found.col) == (0, 1)));
+    }
+
+    #[test]
+    fn test_find_plus_shape() {
+        // Arrange
+        let grid = parse_matrix(".X..\nXXX.\n.XX.\n.XXX\n..X.");
+        let pattern = Pattern::parse(".X.\nXXX\n.X.");
+        // Act
+        let matches = pattern.find(&grid, Symmetry::None);
+        // Assert
+        assert_eq!(
+            matches,
+            vec![
+                PatternMatch {
+                    row: 0,
+                    col: 0,
+                    variant: 0
+                },
+                PatternMatch {
+                    row: 2,
+                    col: 1,
+                    variant: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cells_at() {
+        // Arrange
+        let pattern = Pattern::x_mas();
+        // Act
+        let cells = pattern.cells_at(1, 2);
+        // Assert
+        assert_eq!(cells, vec![(1, 2), (1, 4), (2, 3), (3, 2), (3, 4)]);
+    }
+
+    #[test]
+    fn test_find_wildcard_needs_a_cell() {
+        // Arrange
+        let grid = parse_matrix("AB\nC");
+        let pattern = Pattern::parse("A.\n..");
+        // Act
+        let matches = pattern.find(&grid, Symmetry::None);
+        // Assert
+        assert!(matches.is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Style {
+    /// Letters outside every match become `.`, as in the puzzle statement.
+    Dots,
+    /// All letters are kept and the matched ones are coloured.
+    Color,
+}
+
+/// Which matches `--render` highlights.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Target {
+    Xmas,
+    XMas,
+}
+
+impl Target {
+    pub fn from_arg(value: &str) -> Result<Self, String> {
+        match value {
+            "xmas" => Ok(Target::Xmas),
+            "x-mas" => Ok(Target::XMas),
+            _ => Err(format!(
+                "Unknown render target {}, expected xmas or x-mas",
+                value
+            )),
+        }
+    }
+}
+
+/// The grid with the `highlighted` cells marked according to `style`, one
+/// line per row.
+pub fn render(grid: &[Vec<char>], highlighted: &HashSet<(usize, usize)>, style: Style) -> String {
+    let mut rendered = String::new();
+
+    for (row, line) in grid.iter().enumerate() {
+        for (col, &letter) in line.iter().enumerate() {
+            let matched = highlighted.contains(&(row, col));
+            match (style, matched) {
+                (Style::Dots, true) => rendered.push(letter),
+                (Style::Dots, false) => rendered.push('.'),
+                (Style::Color, true) => {
+                    rendered.push_str(GREEN);
+                    rendered.push(letter);
+                    rendered.push_str(RESET);
+                }
+                (Style::Color, false) => rendered.push(letter),
+            }
+        }
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day04::parse_matrix;
+    use test_case::test_case;
+
+    #[test_case("xmas", Ok(Target::Xmas))]
+    #[test_case("x-mas", Ok(Target::XMas))]
+    #[test_case("mas", Err("Unknown render target mas, expected xmas or x-mas".to_string()))]
+    fn test_target_from_arg(value: &str, expected: Result<Target, String>) {
+        // Arrange
+        // Act
+        let target = Target::from_arg(value);
+        // Assert
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    fn test_render_dots() {
+        // Arrange
+        let grid = parse_matrix("ABC\nDEF");
+        let highlighted = HashSet::from([(0, 1), (1, 2)]);
+        // Act
+        let rendered = render(&grid, &highlighted, Style::Dots);
+        // Assert
+        assert_eq!(rendered, ".B.\n..F\n");
+    }
+
+    #[test]
+    fn test_render_color() {
+        // Arrange
+        let grid = parse_matrix("AB\nC");
+        let highlighted = HashSet::from([(0, 0), (1, 0)]);
+        // Act
+        let rendered = render(&grid, &highlighted, Style::Color);
+        // Assert
+        assert_eq!(rendered, "\x1b[32mA\x1b[0mB\n\x1b[32mC\x1b[0m\n");
+    }
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Row and column step, with rows growing downwards.
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+}
+
+/// A word found starting at `row`, `col` and read towards `direction`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub struct WordMatch {
+    pub row: usize,
+    pub col: usize,
+    pub direction: Direction,
+}
+
+pub struct WordSearch<'a> {
+    grid: &'a [Vec<char>],
+}
+
+impl WordMatch {
+    /// Grid cells covered by a word of `length` letters found here.
+    pub fn cells(&self, length: usize) -> Vec<(usize, usize)> {
+        let (row_step, col_step) = self.direction.delta();
+
+        (0..length as isize)
+            .map(|index| {
+                (
+                    (self.row as isize + row_step * index) as usize,
+                    (self.col as isize + col_step * index) as usize,
+                )
+            })
+            .collect()
+    }
+}
+
+impl<'a> WordSearch<'a> {
+    pub fn new(grid: &'a [Vec<char>]) -> Self {
+        Self { grid }
+    }
+
+    /// Every occurrence of `word` along the given directions, in row-major
+    /// order of the first letter. Rows may have different lengths.
+    pub fn find(&self, word: &str, directions: &[Direction]) -> Vec<WordMatch> {
+        let letters: Vec<char> = word.chars().collect();
+        if letters.is_empty() {
+            return vec![];
+        }
+
+        let mut matches = vec![];
+
+        for (row, line) in self.grid.iter().enumerate() {
+            for col in 0..line.len() {
+                for &direction in directions {
+                    if self.matches_at(&letters, row, col, direction) {
+                        matches.push(WordMatch {
+                            row,
+                            col,
+                            direction,
+                        });
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+
+    fn matches_at(&self, letters: &[char], row: usize, col: usize, direction: Direction) -> bool {
+        let (row_step, col_step) = direction.delta();
+
+        letters.iter().enumerate().all(|(index, letter)| {
+            let row = row as isize + row_step * index as isize;
+            let col = col as isize + col_step * index as isize;
+            self.get(row, col) == Some(letter)
+        })
+    }
+
+    fn get(&self, row: isize, col: isize) -> Option<&char> {
+        let row = usize::try_from(row).ok()?;
+        let col = usize::try_from(col).ok()?;
+        self.grid.get(row).and_then(|line| line.get(col))
+    }
+}
+
+impl fmt::Display for WordMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}) {:?}", self.row, self.col, self.direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day04::{parse_matrix, read_matrix_from_file};
+    use test_case::test_case;
+
+    #[test]
+    fn test_find_xmas_all_directions() {
+        // Arrange
+        let matrix = read_matrix_from_file("data/day04/example.txt");
+        let search = WordSearch::new(&matrix);
+        // Act
+        let matches = search.find("XMAS", &Direction::ALL);
+        // Assert
+        assert_eq!(matches.len(), 18);
+    }
+
+    #[test]
+    fn test_find_positions() {
+        // Arrange
+        let grid = parse_matrix("CAT\nAXA\nTAC");
+        let search = WordSearch::new(&grid);
+        // Act
+        let matches = search.find("CAT", &Direction::ALL);
+        // Assert
+        assert_eq!(
+            matches,
+            vec![
+                WordMatch {
+                    row: 0,
+                    col: 0,
+                    direction: Direction::East
+                },
+                WordMatch {
+                    row: 0,
+                    col: 0,
+                    direction: Direction::South
+                },
+                WordMatch {
+                    row: 2,
+                    col: 2,
+                    direction: Direction::North
+                },
+                WordMatch {
+                    row: 2,
+                    col: 2,
+                    direction: Direction::West
+                },
+            ]
+        );
+    }
+
+    #[test_case(&[Direction::East], 2)]
+    #[test_case(&[Direction::West], 0)]
+    #[test_case(&[Direction::SouthEast, Direction::NorthWest], 1)]
+    #[test_case(&[], 0)]
+    fn test_find_direction_subsets(directions: &[Direction], expected: usize) {
+        // Arrange
+        let grid = parse_matrix("ABC.\n.B.C\n..C.\n.ABC");
+        let search = WordSearch::new(&grid);
+        // Act
+        let matches = search.find("ABC", directions).len();
+        // Assert
+        assert_eq!(matches, expected);
+    }
+
+    #[test]
+    fn test_find_ragged_rows() {
+        // Arrange
+        let grid = parse_matrix("AB\nABC\nA");
+        let search = WordSearch::new(&grid);
+        // Act
+        let matches = search.find("AA", &[Direction::South]);
+        // Assert
+        assert_eq!(matches.len(), 2);
+    }
+
+    #[test]
+    fn test_match_cells() {
+        // Arrange
+        let found = WordMatch {
+            row: 3,
+            col: 1,
+            direction: Direction::NorthEast,
+        };
+        // Act
+        let cells = found.cells(3);
+        // Assert
+        assert_eq!(cells, vec![(3, 1), (2, 2), (1, 3)]);
+    }
+
+    #[test]
+    fn test_find_empty_word() {
+        // Arrange
+        let grid = parse_matrix("AB");
+        let search = WordSearch::new(&grid);
+        // Act
+        let matches = search.find("", &Direction::ALL);
+        // Assert
+        assert!(matches.is_empty());
+    }
+}
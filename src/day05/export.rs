@@ -0,0 +1,181 @@
+use crate::day05::{PageOrderingRules, Rule};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Dot,
+    GraphMl,
+}
+
+impl Format {
+    pub fn from_arg(value: &str) -> Result<Self, String> {
+        match value {
+            "dot" => Ok(Format::Dot),
+            "graphml" => Ok(Format::GraphMl),
+            _ => Err(format!(
+                "Unknown export format {}, expected dot or graphml",
+                value
+            )),
+        }
+    }
+}
+
+/// The rules as a directed graph, with an edge from `before` to `after`.
+/// When built for an update, only its pages are kept and the edges that the
+/// update breaks are marked as violated.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RuleGraph {
+    nodes: Vec<u32>,
+    edges: Vec<(Rule, bool)>,
+}
+
+impl RuleGraph {
+    pub fn new(rules: &PageOrderingRules, update: Option<&[u32]>) -> Self {
+        let Some(pages) = update else {
+            let edges: Vec<(Rule, bool)> = rules
+                .rules()
+                .into_iter()
+                .map(|rule| (rule, false))
+                .collect();
+            let nodes: BTreeSet<u32> = edges
+                .iter()
+                .flat_map(|(rule, _)| [rule.before, rule.after])
+                .collect();
+
+            return Self {
+                nodes: nodes.into_iter().collect(),
+                edges,
+            };
+        };
+
+        let included: BTreeSet<u32> = pages.iter().copied().collect();
+        let violated: Vec<Rule> = rules
+            .violations(pages)
+            .into_iter()
+            .map(|violation| violation.rule)
+            .collect();
+        let edges = rules
+            .rules()
+            .into_iter()
+            .filter(|rule| included.contains(&rule.before) && included.contains(&rule.after))
+            .map(|rule| (rule, violated.contains(&rule)))
+            .collect();
+
+        Self {
+            nodes: included.into_iter().collect(),
+            edges,
+        }
+    }
+
+    /// Graphviz DOT, with violated edges drawn in red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph rules {\n");
+
+        for node in &self.nodes {
+            writeln!(dot, "    {};", node).unwrap();
+        }
+        for (rule, violated) in &self.edges {
+            let style = if *violated { " [color=red]" } else { "" };
+            writeln!(dot, "    {} -> {}{};", rule.before, rule.after, style).unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// GraphML, with a boolean `violated` attribute on every edge.
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  \
+             <key id=\"violated\" for=\"edge\" attr.name=\"violated\" attr.type=\"boolean\">\n    \
+             <default>false</default>\n  \
+             </key>\n  \
+             <graph id=\"rules\" edgedefault=\"directed\">\n",
+        );
+
+        for node in &self.nodes {
+            writeln!(graphml, "    <node id=\"{}\"/>", node).unwrap();
+        }
+        for (rule, violated) in &self.edges {
+            writeln!(
+                graphml,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"violated\">{}</data></edge>",
+                rule.before, rule.after, violated
+            )
+            .unwrap();
+        }
+
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn create_rules() -> PageOrderingRules {
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("1|2").add_order("2|3").add_order("3|4");
+        rules
+    }
+
+    #[test_case("dot", Ok(Format::Dot))]
+    #[test_case("graphml", Ok(Format::GraphMl))]
+    #[test_case("svg", Err("Unknown export format svg, expected dot or graphml".to_string()))]
+    fn test_format_from_arg(value: &str, expected: Result<Format, String>) {
+        // Arrange
+        // Act
+        let format = Format::from_arg(value);
+        // Assert
+        assert_eq!(format, expected);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        // Arrange
+        let graph = RuleGraph::new(&create_rules(), None);
+        // Act
+        let dot = graph.to_dot();
+        // Assert
+        assert_eq!(
+            dot,
+            "digraph rules {\n    1;\n    2;\n    3;\n    4;\n    \
+             1 -> 2;\n    2 -> 3;\n    3 -> 4;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot_for_update() {
+        // Arrange
+        let graph = RuleGraph::new(&create_rules(), Some(&[3, 2, 5]));
+        // Act
+        let dot = graph.to_dot();
+        // Assert
+        assert_eq!(
+            dot,
+            "digraph rules {\n    2;\n    3;\n    5;\n    2 -> 3 [color=red];\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_to_graphml_for_update() {
+        // Arrange
+        let graph = RuleGraph::new(&create_rules(), Some(&[1, 3, 2]));
+        // Act
+        let graphml = graph.to_graphml();
+        // Assert
+        assert!(graphml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml"));
+        assert!(graphml.contains("    <node id=\"3\"/>\n"));
+        assert!(graphml.contains(
+            "<edge source=\"1\" target=\"2\"><data key=\"violated\">false</data></edge>"
+        ));
+        assert!(graphml
+            .contains("<edge source=\"2\" target=\"3\"><data key=\"violated\">true</data></edge>"));
+        assert!(!graphml.contains("source=\"3\""));
+        assert!(graphml.ends_with("  </graph>\n</graphml>\n"));
+    }
+}
@@ -1,81 +1,230 @@
-use std::cmp::Ordering;
-use std::collections::HashMap;
+mod export;
+mod parser;
+
+use crate::day05::export::{Format, RuleGraph};
+use crate::day05::parser::parse_input;
+use crate::utils::option_value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
 
-pub(crate) fn run() {
+pub(crate) fn run(args: &[String]) {
     let filename = "data/day05/input.txt";
-    let (part1, part2) = parse_file(filename);
+    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+    let (rules, part2) = match parse_input(&contents) {
+        Ok(input) => (input.rules, input.updates),
+        Err(errors) => {
+            for error in errors {
+                eprintln!("Error: {}", error);
+            }
+            return;
+        }
+    };
+
+    let transitive = args.iter().any(|arg| arg == "--transitive");
+    let rules_for = |pages: &[u32]| {
+        if transitive {
+            Cow::Owned(rules.transitive_closure_over(pages))
+        } else {
+            Cow::Borrowed(&rules)
+        }
+    };
+
+    if args.iter().any(|arg| arg == "--explain") {
+        print_explanations(rules_for, &part2);
+    }
+    let export = match export_options(args, &part2) {
+        Ok(export) => export,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return;
+        }
+    };
+    if let Some(export) = export {
+        let graph = match export.update {
+            Some(update) => RuleGraph::new(&rules_for(update), Some(update)),
+            None => RuleGraph::new(&rules, None),
+        };
+        match export.format {
+            Format::Dot => print!("{}", graph.to_dot()),
+            Format::GraphMl => print!("{}", graph.to_graphml()),
+        }
+        return;
+    }
 
-    let rules = PageOrderingRules::from_lines(part1);
     let result_part_1: u32 = part2
         .iter()
-        .filter_map(|item| rules.check_pages_to_update(item))
+        .filter_map(|item| rules_for(item).check_pages_to_update(item))
         .sum();
     println!("Result of Day 05, Part 1 is {}", result_part_1);
 
     let result_part_2: u32 = part2
         .iter()
-        .filter(|pages| rules.check_pages_not_ordered(pages))
         .filter_map(|item| {
-            let ordered = rules.order_pages(item);
-            rules.check_pages_to_update(&ordered)
+            let rules = rules_for(item);
+            if !rules.check_pages_not_ordered(item) {
+                return None;
+            }
+            match rules.order_pages(item) {
+                Ok(ordered) => rules.check_pages_to_update(&ordered),
+                Err(error) => {
+                    eprintln!("Error: cannot order pages {:?}: {}", item, error);
+                    None
+                }
+            }
         })
         .sum();
     println!("Result of Day 05, Part 2 is {}", result_part_2);
 }
 
-fn parse_file(filename: &str) -> (Vec<String>, Vec<Vec<u32>>) {
-    let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+/// `--export dot|graphml`, optionally limited to the update chosen with
+/// `--update N`, counting from 1.
+#[derive(Debug, PartialEq, Eq)]
+struct Export<'a> {
+    format: Format,
+    update: Option<&'a [u32]>,
+}
 
-    let mut sections = contents.split("\n\n");
-
-    let part1: Vec<String> = sections
-        .next()
-        .expect("Missing first part")
-        .lines()
-        .map(|line| line.to_string())
-        .collect();
-
-    let part2: Vec<Vec<u32>> = sections
-        .next()
-        .expect("Missing last part")
-        .lines()
-        .map(|line| {
-            line.split(',')
-                .filter_map(|num| num.parse::<u32>().ok())
-                .collect::<Vec<u32>>() // Recolecta en un Vec<u32>
-        })
-        .collect(); // Recolecta todas las líneas en un Vec<Vec<u32>>
+fn export_options<'a>(
+    args: &[String],
+    updates: &'a [Vec<u32>],
+) -> Result<Option<Export<'a>>, String> {
+    let Some(format) = option_value(args, "--export") else {
+        return Ok(None);
+    };
+    let format = Format::from_arg(format)?;
+    let update = match option_value(args, "--update") {
+        Some(number) => {
+            let index = number
+                .parse::<usize>()
+                .map_err(|_| format!("Invalid update number: {}", number))?;
+            let update = index
+                .checked_sub(1)
+                .and_then(|index| updates.get(index))
+                .ok_or_else(|| format!("Update number out of range: {}", number))?;
+            Some(update.as_slice())
+        }
+        None => None,
+    };
+
+    Ok(Some(Export { format, update }))
+}
+
+/// `rules_for` gives the rules that apply to one update.
+fn print_explanations<'a>(
+    rules_for: impl Fn(&[u32]) -> Cow<'a, PageOrderingRules>,
+    updates: &[Vec<u32>],
+) {
+    for (index, pages) in updates.iter().enumerate() {
+        let rules = rules_for(pages);
+        let violations = rules.violations(pages);
+        if violations.is_empty() {
+            continue;
+        }
+
+        println!("Update {} {:?}", index + 1, pages);
+        for violation in &violations {
+            println!("  {}", violation);
+        }
+        match rules.order_pages(pages) {
+            Ok(ordered) => println!("  corrected: {:?}", ordered),
+            Err(error) => println!("  cannot be corrected: {}", error),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OrderingError {
+    /// The page appears more than once in the update.
+    DuplicatePage(u32),
+    /// The rules between these pages, in update order, form a cycle.
+    Cycle(Vec<u32>),
+    /// No rule decides which of these two pages comes first.
+    Ambiguous(u32, u32),
+}
+
+impl fmt::Display for OrderingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderingError::DuplicatePage(page) => write!(f, "page {} appears twice", page),
+            OrderingError::Cycle(pages) => write!(f, "rules form a cycle among {:?}", pages),
+            OrderingError::Ambiguous(first, second) => {
+                write!(f, "no rule orders pages {} and {}", first, second)
+            }
+        }
+    }
+}
+
+/// `before|after`: page `before` must be printed before page `after`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Rule {
+    pub before: u32,
+    pub after: u32,
+}
+
+/// Pages `earlier` and `later` appear in that order in an update, but `rule`
+/// requires the opposite.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct RuleViolation {
+    pub earlier: u32,
+    pub later: u32,
+    pub rule: Rule,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}|{}", self.before, self.after)
+    }
+}
 
-    (part1, part2)
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} comes before {}, breaking rule {}",
+            self.earlier, self.later, self.rule
+        )
+    }
 }
 
-#[derive(PartialEq, Debug)]
+/// Rules kept both as an adjacency list, for walking the graph, and as a
+/// set of `(before, after)` pairs, so checking two pages is a single lookup.
+#[derive(PartialEq, Debug, Clone)]
 pub struct PageOrderingRules {
     rules: HashMap<u32, Vec<u32>>,
+    pairs: HashSet<(u32, u32)>,
 }
 
 impl PageOrderingRules {
     fn new() -> Self {
         Self {
             rules: HashMap::new(),
+            pairs: HashSet::new(),
         }
     }
 
-    fn from_lines(lines: Vec<String>) -> Self {
-        let mut rules = Self::new();
-        for line in lines {
-            rules.add_order(line.as_str());
-        }
+    /// Every distinct rule, sorted by page numbers.
+    fn rules(&self) -> Vec<Rule> {
+        let mut rules: Vec<Rule> = self
+            .pairs
+            .iter()
+            .map(|&(before, after)| Rule { before, after })
+            .collect();
+        rules.sort_by_key(|rule| (rule.before, rule.after));
         rules
     }
 
+    /// Ignores rules that are already known.
     fn add(&mut self, index: u32, value: u32) -> &mut Self {
-        self.rules.entry(index).or_insert(vec![]).push(value);
+        if self.pairs.insert((index, value)) {
+            self.rules.entry(index).or_default().push(value);
+        }
 
         self
     }
 
+    #[allow(dead_code)]
     fn add_order(&mut self, order: &str) -> &mut Self {
         if let Some((index_str, value_str)) = order.split_once("|") {
             if let (Ok(index), Ok(value)) = (index_str.parse::<u32>(), value_str.parse::<u32>()) {
@@ -85,52 +234,183 @@ impl PageOrderingRules {
         panic!("Invalid order format: {}", order);
     }
 
-    fn check_pages_to_update(&self, pages: &Vec<u32>) -> Option<u32> {
-        for pair in pages.windows(2) {
-            if let [from, to] = pair {
-                if !self.check_pages(from, to) {
-                    return None;
+    /// The rules between `pages` only, plus every rule implied by chaining
+    /// them, so `a|b` and `b|c` also give `a|c`. The full rule set may be
+    /// cyclic, as the puzzle input is, so the closure is only meaningful
+    /// within one update. Rules that form a cycle among `pages` end up with
+    /// a page required before itself.
+    fn transitive_closure_over(&self, pages: &[u32]) -> Self {
+        let included: HashSet<u32> = pages.iter().copied().collect();
+        let mut closed = Self::new();
+
+        for &start in pages {
+            let mut reached = HashSet::new();
+            let mut pending: Vec<u32> = self.later_pages(&start, &included).collect();
+            while let Some(page) = pending.pop() {
+                if reached.insert(page) {
+                    pending.extend(self.later_pages(&page, &included));
                 }
             }
+
+            let mut reached: Vec<u32> = reached.into_iter().collect();
+            reached.sort();
+            for page in reached {
+                closed.add(start, page);
+            }
+        }
+
+        closed
+    }
+
+    fn check_pages_to_update(&self, pages: &[u32]) -> Option<u32> {
+        if self.check_pages_not_ordered(pages) {
+            return None;
         }
         pages.get(pages.len() / 2).cloned()
     }
 
     fn check_pages(&self, from: &u32, to: &u32) -> bool {
-        if let Some(rules) = self.rules.get(&to) {
-            return rules.contains(&from) == false;
-        }
+        !self.pairs.contains(&(*to, *from))
+    }
 
-        true
+    /// Whether any two pages, adjacent or not, break a rule.
+    fn check_pages_not_ordered(&self, pages: &[u32]) -> bool {
+        pages.iter().enumerate().any(|(index, from)| {
+            pages[index + 1..]
+                .iter()
+                .any(|to| !self.check_pages(from, to))
+        })
     }
 
-    fn check_pages_not_ordered(&self, pages: &Vec<u32>) -> bool {
-        for pair in pages.windows(2) {
-            if let [from, to] = pair {
-                if !self.check_pages(from, to) {
-                    return true;
+    /// Every pair of pages, adjacent or not, printed in an order that a rule
+    /// forbids, in update order.
+    fn violations(&self, pages: &[u32]) -> Vec<RuleViolation> {
+        let mut violations = vec![];
+
+        for (index, &earlier) in pages.iter().enumerate() {
+            for &later in &pages[index + 1..] {
+                if !self.check_pages(&earlier, &later) {
+                    violations.push(RuleViolation {
+                        earlier,
+                        later,
+                        rule: Rule {
+                            before: later,
+                            after: earlier,
+                        },
+                    });
                 }
             }
         }
-        false
+
+        violations
     }
 
-    fn order_pages(&self, pages: &Vec<u32>) -> Vec<u32> {
-        let mut sorted_pages = pages.clone();
-        sorted_pages.sort_by(|from, to| {
-            if self.check_pages(from, to) {
-                Ordering::Less
-            } else {
-                Ordering::Greater
+    /// Kahn's topological sort over the rules whose both pages are in the
+    /// update. Fails unless the rules determine exactly one order.
+    fn order_pages(&self, pages: &[u32]) -> Result<Vec<u32>, OrderingError> {
+        let mut included = HashSet::new();
+        for &page in pages {
+            if !included.insert(page) {
+                return Err(OrderingError::DuplicatePage(page));
+            }
+        }
+
+        let mut in_degree: HashMap<u32, usize> = pages.iter().map(|&page| (page, 0)).collect();
+        for page in pages {
+            for later in self.later_pages(page, &included) {
+                *in_degree.get_mut(&later).unwrap() += 1;
             }
-        });
-        sorted_pages
+        }
+
+        let mut ready: Vec<u32> = pages
+            .iter()
+            .copied()
+            .filter(|page| in_degree[page] == 0)
+            .collect();
+        let mut ordered = Vec::with_capacity(pages.len());
+
+        while let Some(page) = ready.pop() {
+            if let Some(&other) = ready.first() {
+                return Err(OrderingError::Ambiguous(other, page));
+            }
+            ordered.push(page);
+
+            for later in self.later_pages(&page, &included) {
+                let degree = in_degree.get_mut(&later).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push(later);
+                }
+            }
+        }
+
+        if ordered.len() < pages.len() {
+            let remaining = pages
+                .iter()
+                .copied()
+                .filter(|page| in_degree[page] > 0)
+                .collect();
+            return Err(OrderingError::Cycle(self.find_cycle(pages, &remaining)));
+        }
+
+        Ok(ordered)
+    }
+
+    /// One cycle among `remaining`, the pages left over by the topological
+    /// sort, in update order. Pages that only follow a cycle are left out.
+    /// Every remaining page has a remaining page before it, so walking
+    /// backwards from any of them must come back to a page already seen.
+    fn find_cycle(&self, pages: &[u32], remaining: &HashSet<u32>) -> Vec<u32> {
+        let earlier = |page: u32| {
+            pages
+                .iter()
+                .copied()
+                .find(|&before| remaining.contains(&before) && self.pairs.contains(&(before, page)))
+                .expect("Remaining page without a remaining predecessor")
+        };
+
+        let mut walked = vec![];
+        let mut page = pages
+            .iter()
+            .copied()
+            .find(|page| remaining.contains(page))
+            .expect("No remaining pages");
+        while !walked.contains(&page) {
+            walked.push(page);
+            page = earlier(page);
+        }
+
+        let start = walked.iter().position(|&seen| seen == page).unwrap();
+        let cycle: HashSet<u32> = walked[start..].iter().copied().collect();
+        pages
+            .iter()
+            .copied()
+            .filter(|page| cycle.contains(page))
+            .collect()
+    }
+
+    /// Pages that `page` must precede, limited to `included`.
+    fn later_pages<'a>(
+        &'a self,
+        page: &u32,
+        included: &'a HashSet<u32>,
+    ) -> impl Iterator<Item = u32> + 'a {
+        self.rules
+            .get(page)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(move |later| included.contains(later))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::day05::PageOrderingRules;
+    use crate::day05::export::Format;
+    use crate::day05::{
+        export_options, Export, OrderingError, PageOrderingRules, Rule, RuleViolation,
+    };
+    use crate::utils::args;
     use test_case::test_case;
 
     fn create_page_ordering_rules() -> PageOrderingRules {
@@ -172,6 +452,98 @@ mod tests {
         assert_eq!(rules.rules.get(&97), Some(&vec![13, 61, 47, 29, 53, 75]));
     }
 
+    #[test]
+    fn test_add_order_ignores_duplicates() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        // Act
+        rules.add_order("1|2").add_order("1|3").add_order("1|2");
+        // Assert
+        assert_eq!(rules.rules.get(&1), Some(&vec![2, 3]));
+        assert_eq!(rules.pairs.len(), 2);
+    }
+
+    #[test]
+    fn test_transitive_closure() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("1|2").add_order("2|3").add_order("3|4");
+        // Act
+        let closed = rules.transitive_closure_over(&[4, 3, 2, 1]);
+        // Assert
+        assert_eq!(closed.rules().len(), 6);
+        assert!(!closed.check_pages(&4, &1));
+        assert_eq!(closed.order_pages(&[4, 1]), Ok(vec![1, 4]));
+    }
+
+    #[test]
+    fn test_transitive_closure_of_cycle() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("1|2").add_order("2|1");
+        // Act
+        let closed = rules.transitive_closure_over(&[1, 2]);
+        // Assert
+        assert!(!closed.check_pages(&1, &1));
+        assert_eq!(closed.order_pages(&[1]), Err(OrderingError::Cycle(vec![1])));
+    }
+
+    #[test]
+    fn test_transitive_closure_of_globally_cyclic_rules() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules
+            .add_order("1|2")
+            .add_order("2|3")
+            .add_order("3|4")
+            .add_order("4|1");
+        // Act
+        let closed = rules.transitive_closure_over(&[3, 1, 2]);
+        // Assert
+        assert_eq!(
+            closed.rules(),
+            vec![
+                Rule {
+                    before: 1,
+                    after: 2
+                },
+                Rule {
+                    before: 1,
+                    after: 3
+                },
+                Rule {
+                    before: 2,
+                    after: 3
+                },
+            ]
+        );
+        assert_eq!(closed.order_pages(&[3, 1, 2]), Ok(vec![1, 2, 3]));
+        assert_eq!(closed.check_pages_to_update(&[1, 2, 3]), Some(2));
+    }
+
+    #[test]
+    fn test_rules_are_sorted_and_distinct() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("5|1").add_order("2|3").add_order("5|1");
+        // Act
+        let result = rules.rules();
+        // Assert
+        assert_eq!(
+            result,
+            vec![
+                Rule {
+                    before: 2,
+                    after: 3
+                },
+                Rule {
+                    before: 5,
+                    after: 1
+                },
+            ]
+        );
+    }
+
     #[test_case(29, 13, true)]
     #[test_case(13, 29, false)]
     fn test_rules(from: u32, to: u32, expected: bool) {
@@ -198,6 +570,60 @@ mod tests {
         assert_eq!(result, expected.copied());
     }
 
+    fn violation(earlier: u32, later: u32) -> RuleViolation {
+        RuleViolation {
+            earlier,
+            later,
+            rule: Rule {
+                before: later,
+                after: earlier,
+            },
+        }
+    }
+
+    #[test_case(vec![1, 2, 3], true)]
+    #[test_case(vec![3, 2, 1], false)]
+    #[test_case(vec![2, 3], false)]
+    fn test_pages_not_ordered_checks_all_pairs(pages: Vec<u32>, expected: bool) {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("3|1");
+        // Act
+        let result = rules.check_pages_not_ordered(&pages);
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test_case(vec![75,47,61,53,29], vec![])]
+    #[test_case(vec![75,97,47,61,53], vec![violation(75, 97)])]
+    #[test_case(vec![61,13,29], vec![violation(13, 29)])]
+    #[test_case(vec![97,13,75,29,47], vec![
+        violation(13, 75),
+        violation(13, 29),
+        violation(13, 47),
+        violation(29, 47),
+    ])]
+    fn test_violations(pages: Vec<u32>, expected: Vec<RuleViolation>) {
+        // Arrange
+        let rules = create_page_ordering_rules();
+        // Act
+        let result = rules.violations(&pages);
+        // Assert
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_violations_between_non_adjacent_pages() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("3|1");
+        // Act
+        let result = rules.violations(&[1, 2, 3]);
+        // Assert
+        assert_eq!(result, vec![violation(1, 3)]);
+        assert_eq!(result[0].to_string(), "1 comes before 3, breaking rule 3|1");
+    }
+
     #[test_case(vec![75,47,61,53,29], vec![75,47,61,53,29])]
     #[test_case(vec![97,61,53,29,13], vec![97,61,53,29,13])]
     #[test_case(vec![75,29,13], vec![75,29,13])]
@@ -210,6 +636,67 @@ mod tests {
         // Act
         let result = rules.order_pages(&pages);
         // Assert
-        assert_eq!(result, expected);
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test_case(&["1|2", "2|3", "3|1"], vec![1, 2, 3], OrderingError::Cycle(vec![1, 2, 3]))]
+    #[test_case(&["4|1", "1|2", "2|1"], vec![4, 1, 2], OrderingError::Cycle(vec![1, 2]))]
+    #[test_case(&["1|2", "2|1", "2|3"], vec![1, 2, 3], OrderingError::Cycle(vec![1, 2]))]
+    #[test_case(&["1|2", "2|1", "2|3", "3|4"], vec![4, 3, 2, 1], OrderingError::Cycle(vec![2, 1]))]
+    #[test_case(&["1|2", "1|3"], vec![1, 2, 3], OrderingError::Ambiguous(2, 3))]
+    #[test_case(&[], vec![5, 6], OrderingError::Ambiguous(5, 6))]
+    #[test_case(&["1|2"], vec![1, 2, 1], OrderingError::DuplicatePage(1))]
+    fn test_pages_ordering_errors(orders: &[&str], pages: Vec<u32>, expected: OrderingError) {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        for order in orders {
+            rules.add_order(order);
+        }
+        // Act
+        let result = rules.order_pages(&pages);
+        // Assert
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn test_pages_ordering_ignores_rules_outside_update() {
+        // Arrange
+        let mut rules = PageOrderingRules::new();
+        rules.add_order("1|9").add_order("9|2").add_order("2|9");
+        // Act
+        let result = rules.order_pages(&[2, 1]);
+        // Assert
+        assert_eq!(result, Err(OrderingError::Ambiguous(2, 1)));
+    }
+
+    #[test_case(vec![], vec![])]
+    #[test_case(vec![7], vec![7])]
+    fn test_pages_ordering_trivial(pages: Vec<u32>, expected: Vec<u32>) {
+        // Arrange
+        let rules = PageOrderingRules::new();
+        // Act
+        let result = rules.order_pages(&pages);
+        // Assert
+        assert_eq!(result, Ok(expected));
+    }
+
+    #[test_case("--export dot", Ok(Some(Export { format: Format::Dot, update: None })))]
+    #[test_case("--export graphml --update 2", Ok(Some(Export { format: Format::GraphMl, update: Some(&[3, 4]) })))]
+    #[test_case("--update 2", Ok(None))]
+    #[test_case(
+        "--export svg",
+        Err("Unknown export format svg, expected dot or graphml")
+    )]
+    #[test_case("--export dot --update x", Err("Invalid update number: x"))]
+    #[test_case("--export dot --update 0", Err("Update number out of range: 0"))]
+    #[test_case("--export dot --update 3", Err("Update number out of range: 3"))]
+    fn test_export_options(input: &str, expected: Result<Option<Export>, &str>) {
+        // Arrange
+        let args = args(input);
+        let updates = vec![vec![1, 2], vec![3, 4]];
+        // Act
+        let result = export_options(&args, &updates);
+        // Assert
+        assert_eq!(result, expected.map_err(String::from));
     }
 }
@@ -0,0 +1,197 @@
+use crate::day05::PageOrderingRules;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct PuzzleInput {
+    pub rules: PageOrderingRules,
+    pub updates: Vec<Vec<u32>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    InvalidRule(String),
+    InvalidPage(String),
+    /// An update with this many pages has no single middle page.
+    EvenLength(usize),
+    MissingUpdates,
+}
+
+/// A problem found on a 1-based line of the input.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseErrorKind::InvalidRule(text) => {
+                write!(f, "invalid rule '{}', expected 'X|Y'", text)
+            }
+            ParseErrorKind::InvalidPage(text) => write!(f, "invalid page number '{}'", text),
+            ParseErrorKind::EvenLength(length) => {
+                write!(f, "update has {} pages, expected an odd number", length)
+            }
+            ParseErrorKind::MissingUpdates => {
+                write!(f, "missing blank line before the updates")
+            }
+        }
+    }
+}
+
+/// Reads the rules, a blank line and the updates. Lines may end in CRLF or
+/// carry surrounding whitespace; every malformed line is reported rather
+/// than only the first one.
+pub fn parse_input(contents: &str) -> Result<PuzzleInput, Vec<ParseError>> {
+    let mut rules = PageOrderingRules::new();
+    let mut updates = vec![];
+    let mut errors = vec![];
+    let mut in_updates = false;
+    let mut last_line = 0;
+
+    for (index, line) in contents.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        last_line = number;
+
+        if line.is_empty() {
+            in_updates = true;
+            continue;
+        }
+
+        let parsed = if in_updates {
+            parse_update(line).map(|pages| updates.push(pages))
+        } else {
+            parse_rule(line).map(|(before, after)| {
+                rules.add(before, after);
+            })
+        };
+        if let Err(kind) = parsed {
+            errors.push(ParseError { line: number, kind });
+        }
+    }
+
+    if !in_updates {
+        errors.push(ParseError {
+            line: last_line + 1,
+            kind: ParseErrorKind::MissingUpdates,
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(PuzzleInput { rules, updates })
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_rule(line: &str) -> Result<(u32, u32), ParseErrorKind> {
+    line.split_once('|')
+        .and_then(|(before, after)| {
+            Some((
+                before.trim().parse::<u32>().ok()?,
+                after.trim().parse::<u32>().ok()?,
+            ))
+        })
+        .ok_or_else(|| ParseErrorKind::InvalidRule(line.to_string()))
+}
+
+fn parse_update(line: &str) -> Result<Vec<u32>, ParseErrorKind> {
+    let pages = line
+        .split(',')
+        .map(|page| {
+            let page = page.trim();
+            page.parse::<u32>()
+                .map_err(|_| ParseErrorKind::InvalidPage(page.to_string()))
+        })
+        .collect::<Result<Vec<u32>, ParseErrorKind>>()?;
+
+    if pages.len() % 2 == 0 {
+        return Err(ParseErrorKind::EvenLength(pages.len()));
+    }
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn test_parse_input() {
+        // Arrange
+        let contents = "47|53\n97|13\n\n75,47,61\n97\n";
+        // Act
+        let input = parse_input(contents).unwrap();
+        // Assert
+        assert_eq!(input.rules.rules().len(), 2);
+        assert_eq!(input.updates, vec![vec![75, 47, 61], vec![97]]);
+    }
+
+    #[test]
+    fn test_parse_input_crlf_and_whitespace() {
+        // Arrange
+        let contents = "47|53 \r\n 97 | 13\r\n  \r\n75, 47 ,61\t\r\n\r\n";
+        // Act
+        let input = parse_input(contents).unwrap();
+        // Assert
+        assert!(!input.rules.check_pages(&13, &97));
+        assert!(!input.rules.check_pages(&53, &47));
+        assert_eq!(input.updates, vec![vec![75, 47, 61]]);
+    }
+
+    #[test]
+    fn test_parse_input_reports_every_line() {
+        // Arrange
+        let contents = "47|53\n47-53\n\n75,x,61\n75,47\n1,2,3\n";
+        // Act
+        let errors = parse_input(contents).unwrap_err();
+        // Assert
+        assert_eq!(
+            errors,
+            vec![
+                ParseError {
+                    line: 2,
+                    kind: ParseErrorKind::InvalidRule("47-53".to_string())
+                },
+                ParseError {
+                    line: 4,
+                    kind: ParseErrorKind::InvalidPage("x".to_string())
+                },
+                ParseError {
+                    line: 5,
+                    kind: ParseErrorKind::EvenLength(2)
+                },
+            ]
+        );
+    }
+
+    #[test_case("1|2\n2|3", 3)]
+    #[test_case("", 1)]
+    fn test_parse_input_missing_updates(contents: &str, line: usize) {
+        // Arrange
+        // Act
+        let errors = parse_input(contents).unwrap_err();
+        // Assert
+        assert_eq!(
+            errors,
+            vec![ParseError {
+                line,
+                kind: ParseErrorKind::MissingUpdates
+            }]
+        );
+    }
+
+    #[test_case("1|", "line 1: invalid rule '1|', expected 'X|Y'")]
+    #[test_case("1|2\n\n1,,2", "line 3: invalid page number ''")]
+    #[test_case("\n1,2,3,4", "line 2: update has 4 pages, expected an odd number")]
+    fn test_parse_error_display(contents: &str, expected: &str) {
+        // Arrange
+        // Act
+        let errors = parse_input(contents).unwrap_err();
+        // Assert
+        assert_eq!(errors[0].to_string(), expected);
+    }
+}
@@ -0,0 +1,123 @@
+use crate::day06::lab::Position;
+use crate::day06::Direction;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Guard {
+    pub position: Position,
+    pub direction: Direction,
+}
+
+impl Guard {
+    pub fn new(position: Position, direction: Direction) -> Self {
+        Self {
+            position,
+            direction,
+        }
+    }
+
+    /// The cell in front of the guard, unless that is past the top or left
+    /// edge.
+    pub fn ahead(&self) -> Option<Position> {
+        let (row, col) = self.position;
+        let (row_step, col_step) = self.direction.delta();
+
+        Some((
+            row.checked_add_signed(row_step)?,
+            col.checked_add_signed(col_step)?,
+        ))
+    }
+
+    pub fn turned(&self) -> Self {
+        Self::new(self.position, self.direction.turn())
+    }
+}
+
+/// One bit per cell and direction, recording the guard states already seen.
+/// A `u8` holds all eight directions of a cell.
+#[derive(Debug, Clone)]
+pub struct Visited {
+    cols: usize,
+    directions: Vec<u8>,
+}
+
+impl Visited {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            cols,
+            directions: vec![0; rows * cols],
+        }
+    }
+
+    /// Records `guard`, returning whether this state is new.
+    pub fn insert(&mut self, guard: &Guard) -> bool {
+        let (row, col) = guard.position;
+        let cell = &mut self.directions[row * self.cols + col];
+        let bit = 1 << guard.direction.index();
+        let new = *cell & bit == 0;
+        *cell |= bit;
+        new
+    }
+
+    #[allow(dead_code)]
+    pub fn contains_cell(&self, (row, col): Position) -> bool {
+        self.directions[row * self.cols + col] != 0
+    }
+
+    /// Adds every state seen in `other`, which must cover the same lab.
+    pub fn union(&mut self, other: &Visited) {
+        for (cell, directions) in self.directions.iter_mut().zip(&other.directions) {
+            *cell |= directions;
+        }
+    }
+
+    pub fn cell_count(&self) -> usize {
+        self.directions.iter().filter(|&&cell| cell != 0).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(Direction::Up, Some((0, 1)))]
+    #[test_case(Direction::Left, Some((1, 0)))]
+    #[test_case(Direction::Down, Some((2, 1)))]
+    #[test_case(Direction::DownLeft, Some((2, 0)))]
+    #[test_case(Direction::UpRight, Some((0, 2)))]
+    fn test_ahead(direction: Direction, expected: Option<Position>) {
+        // Arrange
+        let guard = Guard::new((1, 1), direction);
+        // Act
+        let ahead = guard.ahead();
+        // Assert
+        assert_eq!(ahead, expected);
+    }
+
+    #[test_case((0, 3), Direction::Up)]
+    #[test_case((3, 0), Direction::Left)]
+    #[test_case((3, 0), Direction::UpLeft)]
+    fn test_ahead_past_edge(position: Position, direction: Direction) {
+        // Arrange
+        let guard = Guard::new(position, direction);
+        // Act
+        let ahead = guard.ahead();
+        // Assert
+        assert_eq!(ahead, None);
+    }
+
+    #[test]
+    fn test_visited() {
+        // Arrange
+        let mut visited = Visited::new(2, 2);
+        // Act
+        let first = visited.insert(&Guard::new((1, 0), Direction::Up));
+        let turned = visited.insert(&Guard::new((1, 0), Direction::Right));
+        let again = visited.insert(&Guard::new((1, 0), Direction::Up));
+        // Assert
+        assert!(first && turned && !again);
+        assert!(visited.contains_cell((1, 0)));
+        assert!(!visited.contains_cell((0, 0)));
+        assert_eq!(visited.cell_count(), 1);
+    }
+}
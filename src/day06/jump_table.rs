@@ -0,0 +1,199 @@
+use crate::day06::guard::Guard;
+use crate::day06::lab::{Lab, Position};
+use crate::day06::Direction;
+use std::collections::HashSet;
+
+/// The last cell of a straight run, and whether an obstacle follows it
+/// rather than the edge of the lab.
+#[derive(Debug, Clone, Copy)]
+struct End {
+    last: Position,
+    blocked: bool,
+}
+
+/// For every cell and direction, where a straight walk that way ends. Lets
+/// a simulation skip whole runs instead of stepping cell by cell. Only the
+/// four straight directions are covered, stored at half their direction
+/// index. A cell outside the lab, such as one past the end of a short line,
+/// ends a run like the map edge.
+pub struct JumpTable {
+    cols: usize,
+    ends: Vec<[End; 4]>,
+}
+
+impl JumpTable {
+    pub fn new(lab: &Lab) -> Self {
+        let (rows, cols) = (lab.rows(), lab.cols());
+        let unused = End {
+            last: (0, 0),
+            blocked: false,
+        };
+        let mut table = Self {
+            cols,
+            ends: vec![[unused; 4]; rows * cols],
+        };
+
+        for col in 0..cols {
+            table.record(lab, Direction::Up, (0..rows).map(|row| (row, col)));
+            table.record(lab, Direction::Down, (0..rows).rev().map(|row| (row, col)));
+        }
+        for row in 0..rows {
+            table.record(lab, Direction::Left, (0..cols).map(|col| (row, col)));
+            table.record(lab, Direction::Right, (0..cols).rev().map(|col| (row, col)));
+        }
+
+        table
+    }
+
+    /// Fills in `direction` for `cells`, given in the order a guard walking
+    /// that way would meet them last to first.
+    fn record(&mut self, lab: &Lab, direction: Direction, cells: impl Iterator<Item = Position>) {
+        let mut blocked = false;
+        let mut end = None;
+
+        for position in cells {
+            if lab.is_blocked(position) {
+                blocked = true;
+                end = None;
+            } else if !lab.contains(position) {
+                blocked = false;
+                end = None;
+            } else {
+                let end = *end.get_or_insert(End {
+                    last: position,
+                    blocked,
+                });
+                self.ends[position.0 * self.cols + position.1][direction.index() / 2] = end;
+            }
+        }
+    }
+
+    /// Where the guard stops in front of an obstacle when walking from
+    /// `position`, taking the extra `obstacle` into account, or `None` if it
+    /// leaves the lab.
+    pub fn stop(
+        &self,
+        position: Position,
+        direction: Direction,
+        obstacle: Position,
+    ) -> Option<Position> {
+        let end = self.ends[position.0 * self.cols + position.1][direction.index() / 2];
+        let limit = Self::distance(position, end.last, direction).unwrap();
+
+        match Self::distance(position, obstacle, direction) {
+            Some(steps) if steps > 0 && steps <= limit => {
+                Some(Self::advance(position, direction, steps - 1))
+            }
+            _ => end.blocked.then_some(end.last),
+        }
+    }
+
+    /// Whether `guard` never leaves the map once `obstacle` is added.
+    pub fn loops_with_obstacle(&self, mut guard: Guard, obstacle: Position) -> bool {
+        let mut turns = HashSet::new();
+
+        while let Some(stop) = self.stop(guard.position, guard.direction, obstacle) {
+            guard.position = stop;
+            if !turns.insert(guard) {
+                return true;
+            }
+            guard = guard.turned();
+        }
+
+        false
+    }
+
+    /// Number of steps from `from` to `to` walking towards `direction`, if
+    /// `to` lies that way.
+    fn distance(from: Position, to: Position, direction: Direction) -> Option<usize> {
+        match direction {
+            Direction::Up if from.1 == to.1 => from.0.checked_sub(to.0),
+            Direction::Down if from.1 == to.1 => to.0.checked_sub(from.0),
+            Direction::Left if from.0 == to.0 => from.1.checked_sub(to.1),
+            Direction::Right if from.0 == to.0 => to.1.checked_sub(from.1),
+            _ => None,
+        }
+    }
+
+    fn advance(position: Position, direction: Direction, steps: usize) -> Position {
+        let (row, col) = position;
+        let (row_step, col_step) = direction.delta();
+        (
+            row.wrapping_add_signed(row_step * steps as isize),
+            col.wrapping_add_signed(col_step * steps as isize),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    const MAP: &str = "..#..\n....#\n.....\n#..^.\n...#.";
+
+    #[test_case((3, 3), Direction::Up, None)]
+    #[test_case((3, 3), Direction::Right, None)]
+    #[test_case((3, 3), Direction::Down, Some((3, 3)))]
+    #[test_case((3, 3), Direction::Left, Some((3, 1)))]
+    #[test_case((1, 2), Direction::Up, Some((1, 2)))]
+    #[test_case((1, 0), Direction::Right, Some((1, 3)))]
+    fn test_stop(position: Position, direction: Direction, expected: Option<Position>) {
+        // Arrange
+        let table = JumpTable::new(&Lab::from_string(MAP));
+        // Act
+        let stop = table.stop(position, direction, (4, 4));
+        // Assert
+        assert_eq!(stop, expected);
+    }
+
+    #[test_case((3, 3), Direction::Up, (1, 3), Some((2, 3)))]
+    #[test_case((3, 3), Direction::Up, (0, 3), Some((1, 3)))]
+    #[test_case((3, 3), Direction::Right, (3, 4), Some((3, 3)))]
+    #[test_case((3, 3), Direction::Left, (3, 2), Some((3, 3)))]
+    #[test_case((3, 3), Direction::Left, (2, 2), Some((3, 1)))]
+    fn test_stop_with_obstacle(
+        position: Position,
+        direction: Direction,
+        obstacle: Position,
+        expected: Option<Position>,
+    ) {
+        // Arrange
+        let table = JumpTable::new(&Lab::from_string(MAP));
+        // Act
+        let stop = table.stop(position, direction, obstacle);
+        // Assert
+        assert_eq!(stop, expected);
+    }
+
+    #[test_case((1, 4), Direction::Down, None)]
+    #[test_case((1, 0), Direction::Down, None)]
+    #[test_case((2, 0), Direction::Right, None)]
+    #[test_case((3, 3), Direction::Up, None)]
+    #[test_case((3, 1), Direction::Up, Some((1, 1)))]
+    fn test_stop_uneven_lines(
+        position: Position,
+        direction: Direction,
+        expected: Option<Position>,
+    ) {
+        // Arrange
+        let table = JumpTable::new(&Lab::from_string(".#...\n.....\n..\n.....\n....."));
+        // Act
+        let stop = table.stop(position, direction, (4, 4));
+        // Assert
+        assert_eq!(stop, expected);
+    }
+
+    #[test_case((6, 3), true)]
+    #[test_case((7, 6), true)]
+    #[test_case((1, 1), false)]
+    fn test_loops_with_obstacle(obstacle: Position, expected: bool) {
+        // Arrange
+        let lab = Lab::from_file("data/day06/example.txt");
+        let table = JumpTable::new(&lab);
+        // Act
+        let result = table.loops_with_obstacle(lab.start(), obstacle);
+        // Assert
+        assert_eq!(result, expected);
+    }
+}
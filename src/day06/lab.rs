@@ -0,0 +1,121 @@
+use crate::day06::guard::Guard;
+use crate::day06::Direction;
+use std::fs;
+
+pub type Position = (usize, usize);
+
+/// The lab floor plan and where the guards start, in reading order. It
+/// never changes while guards walk, so any number of simulations can share
+/// one `Lab`. Lines may differ in length; `cols` is the longest one, and
+/// cells past the end of a shorter line are outside the lab.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Lab {
+    rows: usize,
+    cols: usize,
+    widths: Vec<usize>,
+    blocked: Vec<bool>,
+    starts: Vec<Guard>,
+}
+
+impl Lab {
+    pub fn from_file(filename: &str) -> Self {
+        let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
+        Self::from_string(&contents)
+    }
+
+    pub fn from_string(contents: &str) -> Self {
+        let lines: Vec<&str> = contents.lines().collect();
+        let rows = lines.len();
+        let widths: Vec<usize> = lines.iter().map(|line| line.chars().count()).collect();
+        let cols = widths.iter().copied().max().unwrap_or(0);
+        let mut blocked = vec![false; rows * cols];
+        let mut starts = vec![];
+
+        for (row, line) in lines.iter().enumerate() {
+            for (col, ch) in line.chars().enumerate() {
+                match ch {
+                    '#' => blocked[row * cols + col] = true,
+                    '^' => starts.push(Guard::new((row, col), Direction::Up)),
+                    '>' => starts.push(Guard::new((row, col), Direction::Right)),
+                    'v' => starts.push(Guard::new((row, col), Direction::Down)),
+                    '<' => starts.push(Guard::new((row, col), Direction::Left)),
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            rows,
+            cols,
+            widths,
+            blocked,
+            starts,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The first guard, or one at the top-left corner facing up if the map
+    /// has none.
+    pub fn start(&self) -> Guard {
+        self.starts
+            .first()
+            .copied()
+            .unwrap_or(Guard::new((0, 0), Direction::Up))
+    }
+
+    pub fn starts(&self) -> &[Guard] {
+        &self.starts
+    }
+
+    pub fn contains(&self, (row, col): Position) -> bool {
+        row < self.rows && col < self.widths[row]
+    }
+
+    pub fn is_blocked(&self, (row, col): Position) -> bool {
+        self.contains((row, col)) && self.blocked[row * self.cols + col]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_string() {
+        // Arrange
+        // Act
+        let lab = Lab::from_string("..#\n.>.\n#");
+        // Assert
+        assert_eq!((lab.rows(), lab.cols()), (3, 3));
+        assert_eq!(lab.start(), Guard::new((1, 1), Direction::Right));
+        assert!(lab.is_blocked((0, 2)));
+        assert!(lab.is_blocked((2, 0)));
+        assert!(!lab.is_blocked((2, 1)));
+        assert!(!lab.is_blocked((3, 0)));
+        assert!(!lab.contains((0, 3)));
+        assert!(!lab.contains((2, 1)));
+    }
+
+    #[test]
+    fn test_from_string_several_guards() {
+        // Arrange
+        // Act
+        let lab = Lab::from_string("v..\n..<\n...");
+        // Assert
+        assert_eq!(
+            lab.starts(),
+            [
+                Guard::new((0, 0), Direction::Down),
+                Guard::new((1, 2), Direction::Left)
+            ]
+        );
+        assert_eq!(lab.start(), lab.starts()[0]);
+    }
+}
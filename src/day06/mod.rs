@@ -1,161 +1,246 @@
-use std::collections::HashMap;
+mod guard;
+mod jump_table;
+mod lab;
+mod replay;
+mod rules;
+
+use crate::day06::guard::{Guard, Visited};
+use crate::day06::jump_table::JumpTable;
+use crate::day06::lab::{Lab, Position};
+use crate::day06::replay::{Replay, ReplayOptions};
+use crate::day06::rules::MovementRules;
+use std::collections::HashSet;
 use std::fs;
+use std::thread;
+use std::time::Duration;
 
-pub(crate) fn run() {
+pub(crate) fn run(args: &[String]) {
     let filename = "data/day06/input.txt";
-    let mut game = Game::from_file(filename);
-    let result_part_1 = game.play();
+    let lab = Lab::from_file(filename);
+    let rules = match MovementRules::from_args(args) {
+        Ok(rules) => rules,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            return;
+        }
+    };
+
+    if args.iter().any(|arg| arg == "--replay") {
+        match ReplayOptions::from_args(args, &lab) {
+            Ok(options) => replay(&lab, rules, &options),
+            Err(error) => eprintln!("Error: {}", error),
+        }
+        return;
+    }
+
+    let result_part_1 = Game::new(&lab).with_rules(rules).play();
     println!("Result of Day 06, Part 1 is {}", result_part_1);
-    let mut game = Game::from_file(filename);
-    let result_part_2 = game.play_loop();
+    if lab.starts().len() > 1 {
+        println!(
+            "Cells patrolled by any of the {} guards: {}",
+            lab.starts().len(),
+            play_all(&lab, rules)
+        );
+    }
+    let result_part_2 = Game::new(&lab).with_rules(rules).play_loop();
     println!("Result of Day 06, Part 2 is {}", result_part_2);
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Direction {
+/// Cells visited by any of the lab's guards. Guards walk independently and
+/// do not block each other.
+fn play_all(lab: &Lab, rules: MovementRules) -> i32 {
+    let mut visited = Visited::new(lab.rows(), lab.cols());
+
+    for &guard in lab.starts() {
+        let mut game = Game::for_guard(lab, guard).with_rules(rules);
+        game.play();
+        visited.union(&game.visited);
+    }
+
+    visited.cell_count() as i32
+}
+
+/// Plays the guard's walk frame by frame, in the terminal or, with
+/// `--frames DIR`, as numbered PPM images.
+fn replay(lab: &Lab, rules: MovementRules, options: &ReplayOptions) {
+    let ReplayOptions {
+        obstacle, delay, ..
+    } = *options;
+    let frames = options.frames.as_deref();
+    if let Some(directory) = frames {
+        fs::create_dir_all(directory).expect("Error creating frames directory");
+    }
+
+    let mut game = Game::new(lab).with_rules(rules);
+    if let Some(obstacle) = obstacle {
+        game = game.with_obstacle(obstacle);
+    }
+    let mut steps = game.steps();
+    let mut replay = Replay::new(lab, obstacle);
+    let mut previous: Option<Guard> = None;
+
+    for (index, guard) in steps.by_ref().enumerate() {
+        if let Some(previous) = previous {
+            replay.record(&previous, &guard);
+        }
+        previous = Some(guard);
+
+        match frames {
+            Some(directory) => {
+                let file = directory.join(format!("frame_{:05}.ppm", index));
+                fs::write(file, replay.ppm(&guard, 4)).expect("Error writing frame");
+            }
+            None => {
+                print!("\x1b[2J\x1b[H{}", replay.frame(&guard));
+                thread::sleep(Duration::from_millis(delay));
+            }
+        }
+    }
+
+    if steps.ended_by_loop() {
+        println!("The guard is stuck in a loop");
+    } else {
+        println!("The guard left the lab");
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Direction {
     Up,
+    UpRight,
+    Right,
+    DownRight,
     Down,
+    DownLeft,
     Left,
-    Right,
+    UpLeft,
 }
 
 impl Direction {
+    /// Clockwise from `Up`, so the index counts eighths of a turn.
+    const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// Quarter turn clockwise.
     fn turn(&self) -> Self {
+        self.rotate(2)
+    }
+
+    /// Turns clockwise by `eighths` of a full turn.
+    fn rotate(&self, eighths: usize) -> Self {
+        Self::ALL[(self.index() + eighths) % 8]
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Direction::Up => 0,
+            Direction::UpRight => 1,
+            Direction::Right => 2,
+            Direction::DownRight => 3,
+            Direction::Down => 4,
+            Direction::DownLeft => 5,
+            Direction::Left => 6,
+            Direction::UpLeft => 7,
+        }
+    }
+
+    /// Row and column step, with rows growing downwards.
+    fn delta(&self) -> (isize, isize) {
         match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
+            Direction::Up => (-1, 0),
+            Direction::UpRight => (-1, 1),
+            Direction::Right => (0, 1),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (1, 0),
+            Direction::DownLeft => (1, -1),
+            Direction::Left => (0, -1),
+            Direction::UpLeft => (-1, -1),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-pub struct Game {
-    position: (usize, usize),
-    direction: Direction,
-    map: HashMap<(usize, usize), char>,
+/// One guard walking through a shared `Lab` under some `MovementRules`,
+/// with an optional extra obstacle laid over the map.
+#[derive(Debug, Clone)]
+pub struct Game<'a> {
+    lab: &'a Lab,
+    rules: MovementRules,
+    guard: Guard,
+    obstacle: Option<Position>,
+    visited: Visited,
     ended: bool,
     ended_by_loop: bool,
 }
 
-impl Game {
-    fn from_game(game: &Game) -> Self {
-        game.clone()
-    }
-
-    fn from_file(filename: &str) -> Game {
-        let contents = fs::read_to_string(filename).expect("Something went wrong reading the file");
-
-        let mut position = (0, 0);
-        let mut direction = Direction::Up;
-        let mut map: HashMap<(usize, usize), char> = HashMap::new();
-
-        for (row, line) in contents.lines().enumerate() {
-            for (col, ch) in line.chars().enumerate() {
-                if let Some(dir) = match ch {
-                    '^' => Some(Direction::Up),
-                    '>' => Some(Direction::Right),
-                    'v' => Some(Direction::Down),
-                    '<' => Some(Direction::Left),
-                    _ => None,
-                } {
-                    position = (row, col);
-                    direction = dir;
-                    map.insert((row, col), 'X'); // Reemplazar símbolo del personaje
-                } else {
-                    map.insert((row, col), ch);
-                }
-            }
+impl<'a> Game<'a> {
+    fn new(lab: &'a Lab) -> Self {
+        Self::for_guard(lab, lab.start())
+    }
+
+    fn for_guard(lab: &'a Lab, guard: Guard) -> Self {
+        let mut visited = Visited::new(lab.rows(), lab.cols());
+        if lab.contains(guard.position) {
+            visited.insert(&guard);
         }
 
         Self {
-            position,
-            direction,
-            map,
+            lab,
+            rules: MovementRules::default(),
+            guard,
+            obstacle: None,
+            visited,
             ended: false,
             ended_by_loop: false,
         }
     }
 
-    #[allow(dead_code)]
-    fn print_grid(&self) {
-        let (max_row, max_col) = self
-            .map
-            .keys()
-            .fold((0, 0), |(max_row, max_col), (row, col)| {
-                (max_row.max(*row), max_col.max(*col))
-            });
-
-        for row in 0..=max_row {
-            for col in 0..=max_col {
-                // Imprimir el valor de la posición en el mapa, o un espacio si no existe
-                let symbol = self.map.get(&(row, col)).unwrap_or(&' ');
-                print!("{}", symbol);
-            }
-            println!();
-        }
-        println!();
-        println!();
+    fn with_rules(mut self, rules: MovementRules) -> Self {
+        self.rules = rules;
+        self
     }
 
-    fn next_position(&self) -> (usize, usize) {
-        let (row, col) = self.position;
-        let next_position = match self.direction {
-            Direction::Up => (row.wrapping_sub(1), col),
-            Direction::Down => (row + 1, col),
-            Direction::Left => (row, col.wrapping_sub(1)),
-            Direction::Right => (row, col + 1),
-        };
-
-        next_position
+    fn with_obstacle(mut self, obstacle: Position) -> Self {
+        self.obstacle = Some(obstacle);
+        self
     }
 
-    fn turn_until_free_path(&mut self) {
-        let next_position = self.next_position();
-
-        match self.map.get(&next_position) {
-            Some('#') => {
-                self.direction = self.direction.turn();
-                self.turn_until_free_path();
-            }
-            _ => {}
-        }
+    fn is_blocked(&self, position: Position) -> bool {
+        self.lab.is_blocked(position) || self.obstacle == Some(position)
     }
 
+    /// Moves one cell forward, or turns once if an obstacle is ahead. The
+    /// game ends when the guard leaves the map, or as a loop when it is back
+    /// on a cell facing the same way as before.
     fn next(&mut self) {
         if self.ended {
             return;
         }
 
-        let next_position = self.next_position();
-
-        match self.map.get_mut(&next_position) {
-            Some('#') => {
-                if self.map.get(&self.position) == Some(&'+') {
-                    self.ended = true;
-                    self.ended_by_loop = true;
-                    return;
-                }
-                self.map.entry(self.position).and_modify(|c| *c = '+');
-                self.turn_until_free_path();
+        match self.rules.ahead(self.lab, &self.guard) {
+            Some(ahead) if self.is_blocked(ahead) => {
+                self.guard.direction = self.rules.turn(self.guard.direction);
             }
-            Some('O') => {
-                self.direction = self.direction.turn();
-            }
-            Some('+') | Some(_) => {
-                self.position = next_position;
-
-                if let Some(cell) = self.map.get_mut(&next_position) {
-                    if *cell == '.' {
-                        *cell = 'X';
-                    }
-                }
+            Some(ahead) => {
+                self.guard.position = ahead;
             }
             None => {
                 self.ended = true;
+                return;
             }
         }
+
+        if !self.visited.insert(&self.guard) {
+            self.ended = true;
+            self.ended_by_loop = true;
+        }
     }
 
     fn play(&mut self) -> i32 {
@@ -163,66 +248,139 @@ impl Game {
             self.next();
         }
 
-        self.map
-            .iter()
-            .filter(|(_, v)| **v == 'X' || **v == '+')
-            .count() as i32
+        self.visited.cell_count() as i32
+    }
+
+    /// Every state of the guard from the current one on, without changing
+    /// this game.
+    fn steps(&self) -> Steps<'a> {
+        Steps {
+            game: self.clone(),
+            started: false,
+        }
     }
 
-    fn play_loop(&mut self) -> i32 {
+    /// Only cells on the guard's original path can change its route. Each
+    /// is tried once, from the state just before the guard first steps on
+    /// it, since the route up to there is unaffected by the new obstacle.
+    /// If the guard already loops, obstacles off its path keep it looping.
+    /// The jump table only knows the default rules; other rules are
+    /// simulated step by step.
+    fn play_loop(&self) -> i32 {
+        let table = (self.rules == MovementRules::default()).then(|| JumpTable::new(self.lab));
+        let mut steps = self.steps();
+        let path: Vec<Guard> = steps.by_ref().collect();
+        let mut tried = HashSet::from([self.guard.position]);
         let mut count = 0;
 
-        for (pos, value) in self.map.iter() {
-            if *value != '.' {
+        for states in path.windows(2) {
+            let (before, candidate) = (states[0], states[1].position);
+            if candidate == before.position || !tried.insert(candidate) {
                 continue;
             }
 
-            let mut loop_game = Game::from_game(self);
-
-            loop_game.map.entry(*pos).and_modify(|c| *c = 'O');
-
-            loop_game.play();
-
-            if loop_game.ended_by_loop {
+            let looped = match &table {
+                Some(table) => table.loops_with_obstacle(before, candidate),
+                None => {
+                    let mut game = Game::for_guard(self.lab, before)
+                        .with_rules(self.rules)
+                        .with_obstacle(candidate);
+                    game.play();
+                    game.ended_by_loop
+                }
+            };
+            if looped {
                 count += 1;
             }
         }
 
+        if steps.ended_by_loop() {
+            for row in 0..self.lab.rows() {
+                for col in 0..self.lab.cols() {
+                    let free = self.lab.contains((row, col)) && !self.is_blocked((row, col));
+                    if free && !tried.contains(&(row, col)) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+
         count
     }
 }
 
+/// Yields the guard after each move or turn. When the guard loops, the
+/// repeated state is the last one yielded.
+pub struct Steps<'a> {
+    game: Game<'a>,
+    started: bool,
+}
+
+impl Steps<'_> {
+    fn ended_by_loop(&self) -> bool {
+        self.game.ended_by_loop
+    }
+}
+
+impl Iterator for Steps<'_> {
+    type Item = Guard;
+
+    fn next(&mut self) -> Option<Guard> {
+        if !self.started {
+            self.started = true;
+            return (!self.game.ended).then_some(self.game.guard);
+        }
+        if self.game.ended {
+            return None;
+        }
+
+        self.game.next();
+        if self.game.ended && !self.game.ended_by_loop {
+            return None;
+        }
+        Some(self.game.guard)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::day06::{Direction, Game};
+    use crate::day06::guard::Guard;
+    use crate::day06::lab::Lab;
+    use crate::day06::replay::Replay;
+    use crate::day06::rules::{MovementRules, Turn};
+    use crate::day06::{play_all, Direction, Game};
+    use test_case::test_case;
 
     #[test]
     fn test_create_game() {
         // Arrange
-        let game = Game::from_file("data/day06/example.txt");
+        let lab = Lab::from_file("data/day06/example.txt");
         // Act
+        let game = Game::new(&lab);
         // Assert
-        assert_eq!(game.position, (6, 4));
-        assert_eq!(game.direction, Direction::Up);
-        assert_eq!(game.map.get(&(6, 4)), Some(&'X'));
+        assert_eq!(game.guard.position, (6, 4));
+        assert_eq!(game.guard.direction, Direction::Up);
+        assert!(game.visited.contains_cell((6, 4)));
     }
 
     #[test]
     fn test_run_move_up() {
         // Arrange
-        let mut game = Game::from_file("data/day06/example.txt");
+        let lab = Lab::from_file("data/day06/example.txt");
+        let mut game = Game::new(&lab);
         // Act
         game.next();
         // Assert
-        assert_eq!(game.position, (5, 4));
-        assert_eq!(game.direction, Direction::Up);
-        assert_eq!(game.map.get(&(5, 4)), Some(&'X'));
+        assert_eq!(game.guard.position, (5, 4));
+        assert_eq!(game.guard.direction, Direction::Up);
+        assert!(game.visited.contains_cell((5, 4)));
     }
 
     #[test]
     fn test_run_turn_right() {
         // Arrange
-        let mut game = Game::from_file("data/day06/example.txt");
+        let lab = Lab::from_file("data/day06/example.txt");
+        let mut game = Game::new(&lab);
         // Act
         game.next();
         game.next();
@@ -231,14 +389,15 @@ mod tests {
         game.next();
         game.next();
         // Assert
-        assert_eq!(game.position, (1, 4));
-        assert_eq!(game.direction, Direction::Right);
+        assert_eq!(game.guard.position, (1, 4));
+        assert_eq!(game.guard.direction, Direction::Right);
     }
 
     #[test]
     fn test_run_game() {
         // Arrange
-        let mut game = Game::from_file("data/day06/example.txt");
+        let lab = Lab::from_file("data/day06/example.txt");
+        let mut game = Game::new(&lab);
         // Act
         let result = game.play();
         // Assert
@@ -248,18 +407,162 @@ mod tests {
     #[test]
     fn test_detect_loop() {
         // Arrange
-        let mut game = Game::from_file("data/day06/example.txt");
-        game.map.entry((6, 3)).and_modify(|c| *c = '#');
+        let lab = Lab::from_file("data/day06/example.txt");
+        let mut game = Game::new(&lab).with_obstacle((6, 3));
         // Act
         game.play();
         // Assert
-        assert_eq!(game.ended_by_loop, true);
+        assert!(game.ended_by_loop);
+        assert!(!lab.is_blocked((6, 3)));
+    }
+
+    #[test_case("", 0, false; "empty map")]
+    #[test_case("....\n.^..\n....", 2, false; "straight exit")]
+    #[test_case(".#.\n.^#\n...", 2, false; "double turn")]
+    #[test_case(".#.\n#^#\n.#.", 1, true; "boxed in")]
+    #[test_case(".#.\n#.#\n#^#\n#.#", 3, false; "u-turn at dead end")]
+    #[test_case("#.#\n#^#\n###", 2, false; "u-turn back out")]
+    #[test_case(".#..\n...#\n#^..\n..#.", 4, true; "square loop")]
+    #[test_case(".#...\n....#\n.^...\n#....\n...#.", 8, true; "loop through start")]
+    #[test_case("..#.\n#..#\n.^#.\n.#..", 3, false; "escape after double turns")]
+    #[test_case(".##.#\n#...#\n..^##", 4, true; "loop with double turns")]
+    fn test_tricky_maps(map: &str, visited: i32, looped: bool) {
+        // Arrange
+        let lab = Lab::from_string(map);
+        let mut game = Game::new(&lab);
+        // Act
+        let result = game.play();
+        // Assert
+        assert_eq!(game.ended_by_loop, looped);
+        assert_eq!(result, visited);
+    }
+
+    #[test]
+    fn test_steps() {
+        // Arrange
+        let lab = Lab::from_string(".#.\n.^#\n...");
+        let game = Game::new(&lab);
+        // Act
+        let steps: Vec<Guard> = game.steps().collect();
+        // Assert
+        assert_eq!(
+            steps,
+            vec![
+                Guard::new((1, 1), Direction::Up),
+                Guard::new((1, 1), Direction::Right),
+                Guard::new((1, 1), Direction::Down),
+                Guard::new((2, 1), Direction::Down),
+            ]
+        );
+        assert_eq!(game.guard, lab.start());
+    }
+
+    #[test]
+    fn test_steps_end_with_repeated_state() {
+        // Arrange
+        let lab = Lab::from_string(".#.\n#^#\n.#.");
+        // Act
+        let mut steps = Game::new(&lab).steps();
+        let states: Vec<Guard> = steps.by_ref().collect();
+        // Assert
+        assert_eq!(states.len(), 5);
+        assert_eq!(states.first(), states.last());
+        assert!(steps.ended_by_loop());
+    }
+
+    #[test]
+    fn test_replay_loop() {
+        // Arrange
+        let lab = Lab::from_file("data/day06/example.txt");
+        let game = Game::new(&lab).with_obstacle((6, 3));
+        let mut replay = Replay::new(&lab, Some((6, 3)));
+        let states: Vec<Guard> = game.steps().collect();
+        // Act
+        for pair in states.windows(2) {
+            replay.record(&pair[0], &pair[1]);
+        }
+        let frame = replay.frame(states.last().unwrap());
+        // Assert
+        assert_eq!(
+            frame,
+            "....#.....\n\
+             ....+---+#\n\
+             ....|...|.\n\
+             ..#.|...|.\n\
+             ....|..#|.\n\
+             ....|...|.\n\
+             .#.O^---+.\n\
+             ........#.\n\
+             #.........\n\
+             ......#...\n"
+        );
+    }
+
+    #[test_case(MovementRules { turn: Turn::Left, ..MovementRules::default() }, 10, false, 0)]
+    #[test_case(MovementRules { turn: Turn::Reverse, ..MovementRules::default() }, 9, false, 3)]
+    #[test_case(MovementRules { diagonal: true, ..MovementRules::default() }, 7, false, 0)]
+    #[test_case(MovementRules { wrap: true, ..MovementRules::default() }, 52, true, 91)]
+    #[test_case(MovementRules { turn: Turn::Left, diagonal: false, wrap: true }, 32, true, 91)]
+    fn test_run_game_with_rules(rules: MovementRules, visited: i32, looped: bool, loops: i32) {
+        // Arrange
+        let lab = Lab::from_file("data/day06/example.txt");
+        let mut game = Game::new(&lab).with_rules(rules);
+        // Act
+        let result = game.play();
+        let result_loops = Game::new(&lab).with_rules(rules).play_loop();
+        // Assert
+        assert_eq!(result, visited);
+        assert_eq!(game.ended_by_loop, looped);
+        assert_eq!(result_loops, loops);
+    }
+
+    #[test]
+    fn test_play_all_guards() {
+        // Arrange
+        let lab = Lab::from_string("v...\n....\n...<");
+        // Act
+        let result = play_all(&lab, MovementRules::default());
+        // Assert
+        assert_eq!(result, 6);
+    }
+
+    #[test]
+    fn test_run_game_uneven_lines() {
+        // Arrange
+        let lab = Lab::from_string(".#....\n.....#\n#^\n......");
+        let mut game = Game::new(&lab);
+        // Act
+        let result = game.play();
+        // Assert
+        assert_eq!(result, 5);
+        assert!(!game.visited.contains_cell((2, 4)));
+    }
+
+    #[test]
+    fn test_run_game_loop_uneven_lines() {
+        // Arrange
+        let lab = Lab::from_string("..#...\n.....#\n.^..\n#.....\n....#.");
+        let expected = (0..lab.rows())
+            .flat_map(|row| (0..lab.cols()).map(move |col| (row, col)))
+            .filter(|&position| lab.contains(position) && !lab.is_blocked(position))
+            .filter(|&position| position != lab.start().position)
+            .filter(|&position| {
+                let mut game = Game::new(&lab).with_obstacle(position);
+                game.play();
+                game.ended_by_loop
+            })
+            .count();
+        // Act
+        let result = Game::new(&lab).play_loop();
+        // Assert
+        assert_eq!(result, expected as i32);
     }
 
     #[test]
     fn test_run_game_loop() {
         // Arrange
-        let mut game = Game::from_file("data/day06/example.txt");
+        let lab = Lab::from_file("data/day06/example.txt");
+        let game = Game::new(&lab);
         // Act
         let result = game.play_loop();
         // Assert
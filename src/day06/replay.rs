@@ -0,0 +1,310 @@
+use crate::day06::guard::Guard;
+use crate::day06::lab::{Lab, Position};
+use crate::day06::Direction;
+use crate::utils::option_value;
+use std::path::PathBuf;
+
+const FLOOR: [u8; 3] = [32, 32, 48];
+const WALL: [u8; 3] = [160, 160, 160];
+const PATH: [u8; 3] = [230, 200, 60];
+const CROSSING: [u8; 3] = [240, 120, 40];
+const OBSTACLE: [u8; 3] = [220, 40, 40];
+const GUARD: [u8; 3] = [60, 220, 80];
+const OUTSIDE: [u8; 3] = [0, 0, 0];
+
+const VERTICAL: u8 = 1;
+const HORIZONTAL: u8 = 2;
+const RISING: u8 = 4;
+const FALLING: u8 = 8;
+const TURN: u8 = 16;
+
+/// `--obstacle ROW,COL` to place before replaying, `--delay MS` between
+/// terminal frames and `--frames DIR` to write PPM images instead. The
+/// obstacle must go on a free cell of `lab` other than the guard's start.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ReplayOptions {
+    pub obstacle: Option<Position>,
+    pub delay: u64,
+    pub frames: Option<PathBuf>,
+}
+
+impl ReplayOptions {
+    pub fn from_args(args: &[String], lab: &Lab) -> Result<Self, String> {
+        let obstacle = match option_value(args, "--obstacle") {
+            Some(value) => {
+                let (row, col) = value
+                    .split_once(',')
+                    .ok_or_else(|| format!("Obstacle must be ROW,COL: {}", value))?;
+                let row = row
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid obstacle row: {}", row))?;
+                let col = col
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid obstacle column: {}", col))?;
+                if !lab.contains((row, col)) {
+                    return Err(format!("Obstacle {},{} is outside the lab", row, col));
+                }
+                if lab.is_blocked((row, col)) {
+                    return Err(format!("Obstacle {},{} is already blocked", row, col));
+                }
+                if lab.start().position == (row, col) {
+                    return Err(format!("Obstacle {},{} is on the guard's start", row, col));
+                }
+                Some((row, col))
+            }
+            None => None,
+        };
+        let delay = match option_value(args, "--delay") {
+            Some(value) => value
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid delay: {}", value))?,
+            None => 50,
+        };
+
+        Ok(Self {
+            obstacle,
+            delay,
+            frames: option_value(args, "--frames").map(PathBuf::from),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Mark {
+    Outside,
+    Floor,
+    Wall,
+    Obstacle,
+    Vertical,
+    Horizontal,
+    Rising,
+    Falling,
+    Turn,
+    Guard(Direction),
+}
+
+/// The trail a guard leaves on the lab, drawn like the puzzle statement:
+/// `|` and `-` for straight moves, `/` and `\` for diagonal ones, `+` where
+/// it turned or crossed its own path, and `O` for the placed obstacle.
+pub struct Replay<'a> {
+    lab: &'a Lab,
+    obstacle: Option<Position>,
+    trails: Vec<u8>,
+}
+
+impl<'a> Replay<'a> {
+    pub fn new(lab: &'a Lab, obstacle: Option<Position>) -> Self {
+        Self {
+            lab,
+            obstacle,
+            trails: vec![0; lab.rows() * lab.cols()],
+        }
+    }
+
+    /// Adds the step from `previous` to `current` to the trail.
+    pub fn record(&mut self, previous: &Guard, current: &Guard) {
+        if previous.position == current.position {
+            let index = self.index(current.position);
+            self.trails[index] |= TURN;
+            return;
+        }
+
+        let trail = match current.direction {
+            Direction::Up | Direction::Down => VERTICAL,
+            Direction::Left | Direction::Right => HORIZONTAL,
+            Direction::UpRight | Direction::DownLeft => RISING,
+            Direction::DownRight | Direction::UpLeft => FALLING,
+        };
+        for position in [previous.position, current.position] {
+            let index = self.index(position);
+            self.trails[index] |= trail;
+        }
+    }
+
+    /// The lab as text, one line per row, with the guard drawn as an arrow.
+    pub fn frame(&self, guard: &Guard) -> String {
+        let mut frame = String::new();
+
+        for row in 0..self.lab.rows() {
+            for col in 0..self.lab.cols() {
+                frame.push(match self.mark((row, col), guard) {
+                    Mark::Outside => continue,
+                    Mark::Floor => '.',
+                    Mark::Wall => '#',
+                    Mark::Obstacle => 'O',
+                    Mark::Vertical => '|',
+                    Mark::Horizontal => '-',
+                    Mark::Rising => '/',
+                    Mark::Falling => '\\',
+                    Mark::Turn => '+',
+                    Mark::Guard(Direction::Up) => '^',
+                    Mark::Guard(Direction::UpRight) => '↗',
+                    Mark::Guard(Direction::Right) => '>',
+                    Mark::Guard(Direction::DownRight) => '↘',
+                    Mark::Guard(Direction::Down) => 'v',
+                    Mark::Guard(Direction::DownLeft) => '↙',
+                    Mark::Guard(Direction::Left) => '<',
+                    Mark::Guard(Direction::UpLeft) => '↖',
+                });
+            }
+            frame.push('\n');
+        }
+
+        frame
+    }
+
+    /// The lab as a binary PPM image with `scale` pixels per cell.
+    pub fn ppm(&self, guard: &Guard, scale: usize) -> Vec<u8> {
+        let (width, height) = (self.lab.cols() * scale, self.lab.rows() * scale);
+        let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+
+        for row in 0..height {
+            for col in 0..width {
+                let colour = match self.mark((row / scale, col / scale), guard) {
+                    Mark::Outside => OUTSIDE,
+                    Mark::Floor => FLOOR,
+                    Mark::Wall => WALL,
+                    Mark::Obstacle => OBSTACLE,
+                    Mark::Vertical | Mark::Horizontal | Mark::Rising | Mark::Falling => PATH,
+                    Mark::Turn => CROSSING,
+                    Mark::Guard(_) => GUARD,
+                };
+                image.extend_from_slice(&colour);
+            }
+        }
+
+        image
+    }
+
+    fn mark(&self, position: Position, guard: &Guard) -> Mark {
+        let index = self.index(position);
+
+        if !self.lab.contains(position) {
+            Mark::Outside
+        } else if guard.position == position {
+            Mark::Guard(guard.direction)
+        } else if self.obstacle == Some(position) {
+            Mark::Obstacle
+        } else if self.lab.is_blocked(position) {
+            Mark::Wall
+        } else {
+            match self.trails[index] {
+                0 => Mark::Floor,
+                VERTICAL => Mark::Vertical,
+                HORIZONTAL => Mark::Horizontal,
+                RISING => Mark::Rising,
+                FALLING => Mark::Falling,
+                _ => Mark::Turn,
+            }
+        }
+    }
+
+    fn index(&self, (row, col): Position) -> usize {
+        row * self.lab.cols() + col
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::args;
+    use test_case::test_case;
+
+    #[test]
+    fn test_options_from_args() {
+        // Arrange
+        let input = args("--replay --obstacle 6,3 --delay 10 --frames out");
+        let lab = Lab::from_file("data/day06/example.txt");
+        // Act
+        let options = ReplayOptions::from_args(&input, &lab);
+        // Assert
+        assert_eq!(
+            options,
+            Ok(ReplayOptions {
+                obstacle: Some((6, 3)),
+                delay: 10,
+                frames: Some(PathBuf::from("out")),
+            })
+        );
+    }
+
+    #[test_case("--obstacle 6", "Obstacle must be ROW,COL: 6")]
+    #[test_case("--obstacle x,3", "Invalid obstacle row: x")]
+    #[test_case("--obstacle 6,-3", "Invalid obstacle column: -3")]
+    #[test_case("--delay fast", "Invalid delay: fast")]
+    #[test_case("--obstacle 99,99", "Obstacle 99,99 is outside the lab")]
+    #[test_case("--obstacle 0,4", "Obstacle 0,4 is already blocked")]
+    #[test_case("--obstacle 6,4", "Obstacle 6,4 is on the guard's start")]
+    fn test_options_from_args_invalid(input: &str, expected: &str) {
+        // Arrange
+        let input = args(input);
+        let lab = Lab::from_file("data/day06/example.txt");
+        // Act
+        let options = ReplayOptions::from_args(&input, &lab);
+        // Assert
+        assert_eq!(options, Err(expected.to_string()));
+    }
+
+    #[test]
+    fn test_frame_start() {
+        // Arrange
+        let lab = Lab::from_file("data/day06/example.txt");
+        let replay = Replay::new(&lab, Some((6, 3)));
+        // Act
+        let frame = replay.frame(&lab.start());
+        // Assert
+        assert_eq!(frame.lines().nth(6), Some(".#.O^....."));
+    }
+
+    #[test]
+    fn test_frame_turn() {
+        // Arrange
+        let lab = Lab::from_string("...\n.^#\n...");
+        let start = lab.start();
+        let mut replay = Replay::new(&lab, None);
+        // Act
+        replay.record(&start, &start.turned());
+        let frame = replay.frame(&Guard::new((0, 1), Direction::Up));
+        // Assert
+        assert_eq!(frame, ".^.\n.+#\n...\n");
+    }
+
+    #[test]
+    fn test_frame_diagonal() {
+        // Arrange
+        let lab = Lab::from_string("...\n...\n...");
+        let mut replay = Replay::new(&lab, None);
+        let guard = Guard::new((1, 1), Direction::UpRight);
+        // Act
+        replay.record(&Guard::new((2, 0), Direction::UpRight), &guard);
+        let frame = replay.frame(&guard);
+        // Assert
+        assert_eq!(frame, "...\n.↗.\n/..\n");
+    }
+
+    #[test]
+    fn test_frame_uneven_lines() {
+        // Arrange
+        let lab = Lab::from_string("..\n.^.\n.");
+        let replay = Replay::new(&lab, None);
+        // Act
+        let frame = replay.frame(&lab.start());
+        // Assert
+        assert_eq!(frame, "..\n.^.\n.\n");
+    }
+
+    #[test]
+    fn test_ppm() {
+        // Arrange
+        let lab = Lab::from_string("#.\n.^");
+        let replay = Replay::new(&lab, None);
+        // Act
+        let image = replay.ppm(&lab.start(), 2);
+        // Assert
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 4 * 4 * 3);
+        assert_eq!(image[header.len()..header.len() + 3], WALL);
+        assert_eq!(image[image.len() - 3..], GUARD);
+    }
+}
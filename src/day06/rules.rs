@@ -0,0 +1,163 @@
+use crate::day06::guard::Guard;
+use crate::day06::lab::{Lab, Position};
+use crate::day06::Direction;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Turn {
+    Left,
+    Right,
+    Reverse,
+}
+
+/// How guards move: which way they turn at an obstacle, whether they also
+/// use the diagonals (turning by an eighth instead of a quarter), and
+/// whether leaving one edge brings them back on the opposite one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MovementRules {
+    pub turn: Turn,
+    pub diagonal: bool,
+    pub wrap: bool,
+}
+
+impl Default for MovementRules {
+    fn default() -> Self {
+        Self {
+            turn: Turn::Right,
+            diagonal: false,
+            wrap: false,
+        }
+    }
+}
+
+impl MovementRules {
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut rules = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--turn" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for {}", arg))?;
+                    rules.turn = match value.as_str() {
+                        "left" => Turn::Left,
+                        "right" => Turn::Right,
+                        "reverse" => Turn::Reverse,
+                        other => return Err(format!("Invalid turn: {}", other)),
+                    }
+                }
+                "--diagonal" => rules.diagonal = true,
+                "--wrap" => rules.wrap = true,
+                _ => {}
+            }
+        }
+
+        Ok(rules)
+    }
+
+    pub fn turn(&self, direction: Direction) -> Direction {
+        let step = if self.diagonal { 1 } else { 2 };
+
+        match self.turn {
+            Turn::Right => direction.rotate(step),
+            Turn::Left => direction.rotate(8 - step),
+            Turn::Reverse => direction.rotate(4),
+        }
+    }
+
+    /// The cell the guard would step into, or `None` if it would leave the
+    /// lab. Only the target cell matters, so diagonal moves may slip
+    /// between two obstacles. Wrapping happens at the edges of the lab's
+    /// bounding box; a missing cell at the end of a short line is still
+    /// outside.
+    pub fn ahead(&self, lab: &Lab, guard: &Guard) -> Option<Position> {
+        if !self.wrap {
+            return guard.ahead().filter(|&position| lab.contains(position));
+        }
+        if lab.rows() == 0 || lab.cols() == 0 {
+            return None;
+        }
+
+        let (row, col) = guard.position;
+        let (row_step, col_step) = guard.direction.delta();
+        Some((
+            (row as isize + row_step).rem_euclid(lab.rows() as isize) as usize,
+            (col as isize + col_step).rem_euclid(lab.cols() as isize) as usize,
+        ))
+        .filter(|&position| lab.contains(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::args;
+    use test_case::test_case;
+
+    #[test_case("", MovementRules::default())]
+    #[test_case("--turn left --wrap", MovementRules { turn: Turn::Left, diagonal: false, wrap: true })]
+    #[test_case("--diagonal --turn reverse", MovementRules { turn: Turn::Reverse, diagonal: true, wrap: false })]
+    fn test_from_args(input: &str, expected: MovementRules) {
+        // Arrange
+        let input = args(input);
+        // Act
+        let rules = MovementRules::from_args(&input);
+        // Assert
+        assert_eq!(rules, Ok(expected));
+    }
+
+    #[test_case("--turn"; "missing value")]
+    #[test_case("--turn up"; "invalid value")]
+    fn test_from_args_invalid(input: &str) {
+        // Arrange
+        let input = args(input);
+        // Act
+        let rules = MovementRules::from_args(&input);
+        // Assert
+        assert!(rules.is_err());
+    }
+
+    #[test_case(Turn::Right, false, Direction::Right)]
+    #[test_case(Turn::Left, false, Direction::Left)]
+    #[test_case(Turn::Reverse, false, Direction::Down)]
+    #[test_case(Turn::Right, true, Direction::UpRight)]
+    #[test_case(Turn::Left, true, Direction::UpLeft)]
+    #[test_case(Turn::Reverse, true, Direction::Down)]
+    fn test_turn(turn: Turn, diagonal: bool, expected: Direction) {
+        // Arrange
+        let rules = MovementRules {
+            turn,
+            diagonal,
+            wrap: false,
+        };
+        // Act
+        let direction = rules.turn(Direction::Up);
+        // Assert
+        assert_eq!(direction, expected);
+    }
+
+    #[test_case((0, 0), Direction::Up, false, None)]
+    #[test_case((1, 2), Direction::Right, false, None)]
+    #[test_case((0, 0), Direction::Up, true, Some((1, 0)))]
+    #[test_case((1, 2), Direction::Right, true, Some((1, 0)))]
+    #[test_case((0, 0), Direction::UpLeft, true, Some((1, 2)))]
+    #[test_case((1, 1), Direction::DownRight, true, Some((0, 2)))]
+    fn test_ahead(
+        position: Position,
+        direction: Direction,
+        wrap: bool,
+        expected: Option<Position>,
+    ) {
+        // Arrange
+        let lab = Lab::from_string("...\n...");
+        let rules = MovementRules {
+            wrap,
+            ..MovementRules::default()
+        };
+        // Act
+        let ahead = rules.ahead(&lab, &Guard::new(position, direction));
+        // Assert
+        assert_eq!(ahead, expected);
+    }
+}
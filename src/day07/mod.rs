@@ -64,7 +64,7 @@ impl Equation {
     }
 
     pub fn is_valid(&self) -> bool {
-        return self.calculate_formula().contains(&self.result);
+        self.calculate_formula().contains(&self.result)
     }
 
     pub fn calculate_formula(&self) -> Vec<i64> {
@@ -108,8 +108,7 @@ impl Equation {
                 }
 
                 if self.operations.contains(Operation::CONCATENATION) {
-                    let concat_group =
-                        Equation::create_operation(&group, |a, b| concatenate_numbers(a, b));
+                    let concat_group = Equation::create_operation(&group, concatenate_numbers);
                     if let Some(first) = concat_group.first() {
                         if *first <= self.result {
                             next.push(concat_group);
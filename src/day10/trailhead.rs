@@ -13,7 +13,7 @@ impl TrailHead {
         let mut map: HashMap<Coord, i32> = HashMap::new();
 
         if let Ok(lines) = read_lines(filename) {
-            for (row_index, line) in lines.flatten().enumerate() {
+            for (row_index, line) in lines.map_while(Result::ok).enumerate() {
                 if let Ok((_, parsed_line)) = parse_levels(&line) {
                     for (col_index, cell) in parsed_line.into_iter().enumerate() {
                         if let Some(value) = cell {
@@ -37,7 +37,7 @@ impl TrailHead {
     pub fn count_all_multiple_trails(&self) -> i32 {
         self.get_starts()
             .iter()
-            .map(|&pos| self.find_multiple_paths(pos) as i32)
+            .map(|&pos| self.find_multiple_paths(pos))
             .sum()
     }
 
@@ -168,7 +168,7 @@ mod tests {
         // Act
         let starts = trail_head.get_starts();
         // Assert
-        assert_eq!(starts.iter().count(), 1);
+        assert_eq!(starts.len(), 1);
     }
 
     #[test]
@@ -178,7 +178,7 @@ mod tests {
         // Act
         let starts = trail_head.get_starts();
         // Assert
-        assert_eq!(starts.iter().count(), 9);
+        assert_eq!(starts.len(), 9);
     }
 
     #[test]
@@ -20,14 +20,15 @@ fn main() {
     } else {
         10
     };
+    let options = args.get(2..).unwrap_or(&[]);
 
     match day_to_run {
         1 => day01::run(),
-        2 => day02::run(),
-        3 => day03::run(),
-        4 => day04::run(),
-        5 => day05::run(),
-        6 => day06::run(),
+        2 => day02::run(options),
+        3 => day03::run(options),
+        4 => day04::run(options),
+        5 => day05::run(options),
+        6 => day06::run(options),
         7 => day07::run(),
         8 => day08::run(),
         9 => day09::run(),
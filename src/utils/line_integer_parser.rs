@@ -1,29 +1,19 @@
 use nom::{
     branch::alt,
-    character::complete::{char, digit1, multispace0, multispace1, one_of},
-    combinator::{map, map_res, value},
+    character::complete::{char, digit1, multispace1, one_of},
+    combinator::{map, map_res, opt, recognize, value},
     multi::{many1, separated_list0},
-    sequence::tuple,
+    sequence::pair,
     IResult,
 };
+use std::str::FromStr;
 
-fn str_to_i32(input: &str) -> Result<i32, std::num::ParseIntError> {
-    input.parse::<i32>()
-}
-
-pub fn parse_two_integers(input: &str) -> IResult<&str, (i32, i32)> {
-    let (input, _) = multispace0(input)?;
-    let (input, (left, _, right)) = tuple((
-        map_res(digit1, str_to_i32),
-        multispace0,
-        map_res(digit1, str_to_i32),
-    ))(input)?;
-
-    Ok((input, (left, right)))
-}
+/// Whitespace separated integers, each with an optional sign. A `-` only
+/// parses for signed `T`.
+pub fn parse_line_of_integers<T: FromStr>(input: &str) -> IResult<&str, Vec<T>> {
+    let integer = recognize(pair(opt(one_of("+-")), digit1));
 
-pub fn parse_line_of_integers(input: &str) -> IResult<&str, Vec<i32>> {
-    separated_list0(multispace1, map_res(digit1, str_to_i32))(input)
+    separated_list0(multispace1, map_res(integer, str::parse::<T>))(input)
 }
 
 pub fn parse_levels(input: &str) -> IResult<&str, Vec<Option<i32>>> {
@@ -41,24 +31,55 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_two_integers_parser() {
+    fn test_parse_line_of_integers() {
+        // Arrange
+        let input = "1 2 3 4 5";
+        // Act
+        let (_, result) = parse_line_of_integers::<i32>(input).unwrap();
+        // Arrange
+        assert_eq!(result, vec![1, 2, 3, 4, 5])
+    }
+
+    #[test]
+    fn test_parse_line_of_integers_wide_type() {
         // Arrange
-        let input = "10    100";
+        let input = "18446744073709551615   1";
         // Act
-        let (_, (left, right)) = parse_two_integers(&input).unwrap();
+        let (_, result) = parse_line_of_integers::<u64>(input).unwrap();
         // Assert
-        assert_eq!(left, 10);
-        assert_eq!(right, 100);
+        assert_eq!(result, vec![u64::MAX, 1]);
     }
 
     #[test]
-    fn test_parse_line_of_integers() {
+    fn test_parse_line_of_integers_out_of_range() {
         // Arrange
-        let input = "1 2 3 4 5";
+        let input = "4294967296 1";
         // Act
-        let (_, result) = parse_line_of_integers(&input).unwrap();
+        let (rest, result) = parse_line_of_integers::<i32>(input).unwrap();
+        // Assert
+        assert_eq!(rest, input);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_parse_line_of_integers_signed() {
         // Arrange
-        assert_eq!(result, vec![1, 2, 3, 4, 5])
+        let input = "-3 +4 -9223372036854775808";
+        // Act
+        let (_, result) = parse_line_of_integers::<i64>(input).unwrap();
+        // Assert
+        assert_eq!(result, vec![-3, 4, i64::MIN]);
+    }
+
+    #[test]
+    fn test_parse_line_of_integers_negative_unsigned() {
+        // Arrange
+        let input = "-3 4";
+        // Act
+        let (rest, result) = parse_line_of_integers::<u64>(input).unwrap();
+        // Assert
+        assert_eq!(rest, input);
+        assert!(result.is_empty());
     }
 
     #[test]
@@ -66,7 +87,7 @@ mod tests {
         // Arrange
         let input = "1.345";
         // Act
-        let (_, result) = parse_levels(&input).unwrap();
+        let (_, result) = parse_levels(input).unwrap();
         // Arrange
         assert_eq!(result, vec![Some(1), None, Some(3), Some(4), Some(5)])
     }
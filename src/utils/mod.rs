@@ -11,3 +11,35 @@ where
     let file = File::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
+
+/// The argument following `name`, for options written as `--name VALUE`.
+pub fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|index| args.get(index + 1))
+        .map(String::as_str)
+}
+
+/// Splits a command line written as one string into arguments.
+#[cfg(test)]
+pub fn args(input: &str) -> Vec<String> {
+    input.split_whitespace().map(String::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case("--find XMAS --color", "--find", Some("XMAS"))]
+    #[test_case("--color --find", "--find", None)]
+    #[test_case("--color", "--find", None)]
+    fn test_option_value(input: &str, name: &str, expected: Option<&str>) {
+        // Arrange
+        let args = args(input);
+        // Act
+        let value = option_value(&args, name);
+        // Assert
+        assert_eq!(value, expected);
+    }
+}